@@ -0,0 +1,163 @@
+//! Runs any day's puzzle solution: `aoc run --day N --part P [input]`.
+#![warn(clippy::unwrap_used)]
+
+use solution::Solution;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Every day's solution. Adding a day only takes adding it here.
+fn solutions() -> Vec<Box<dyn Solution>> {
+    vec![
+        Box::new(day1::Day1),
+        Box::new(day2::Day2),
+        Box::new(day3::Day3),
+        Box::new(day4::Day4),
+        Box::new(day5::Day5),
+    ]
+}
+
+const USAGE: &str = "Usage: aoc run --day N --part P [input]";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn parse_args_reads_the_day_part_and_input() {
+        let result = parse_args(&args("run --part 2 --day 5 some/input"));
+
+        assert_eq!(
+            result,
+            Ok(RunArgs {
+                day: 5,
+                part: 2,
+                input: Some(PathBuf::from("some/input")),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_rejects_missing_or_invalid_options() {
+        assert!(parse_args(&args("run --day 1")).is_err());
+        assert!(parse_args(&args("run --day one --part 1")).is_err());
+        assert!(parse_args(&args("run --day 1 --part 3")).is_err());
+        assert!(parse_args(&args("walk --day 1 --part 1")).is_err());
+    }
+
+    #[test]
+    fn every_day_is_registered_once() {
+        let days = solutions().iter().map(|s| s.day()).collect::<Vec<_>>();
+
+        assert_eq!(days, vec![1, 2, 3, 4, 5]);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct RunArgs {
+    day: u32,
+    part: u32,
+    input: Option<PathBuf>,
+}
+
+fn parse_args(args: &[String]) -> Result<RunArgs, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err("Please supply a command".to_owned());
+    };
+    if command != "run" {
+        return Err(format!("Unknown command '{command}'"));
+    }
+
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--day" | "--part" => {
+                let value = rest
+                    .next()
+                    .ok_or_else(|| format!("Please supply a number after {arg}"))?
+                    .parse::<u32>()
+                    .map_err(|err| format!("Error parsing {arg}: {err}"))?;
+                if arg == "--day" {
+                    day = Some(value);
+                } else {
+                    part = Some(value);
+                }
+            }
+            path if input.is_none() && !path.starts_with("--") => {
+                input = Some(PathBuf::from(path));
+            }
+            other => return Err(format!("Unexpected argument '{other}'")),
+        }
+    }
+
+    let day = day.ok_or("Please supply a day with --day")?;
+    let part = part.ok_or("Please supply a part with --part")?;
+    if part != 1 && part != 2 {
+        return Err(format!("There is no part {part}, only parts 1 and 2"));
+    }
+
+    Ok(RunArgs { day, part, input })
+}
+
+/// Finds the puzzle input for the day and part when none was supplied, e.g. `day5/input/part2`, falling back to the
+/// day's part 1 input since most days share one input between both parts.
+fn default_input(day: u32, part: u32) -> PathBuf {
+    let input_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(format!("day{day}"))
+        .join("input");
+    let for_part = input_dir.join(format!("part{part}"));
+    if for_part.exists() {
+        for_part
+    } else {
+        input_dir.join("part1")
+    }
+}
+
+fn run(args: &RunArgs) -> Result<(), Box<dyn Error>> {
+    let solutions = solutions();
+    let solution = solutions
+        .iter()
+        .find(|s| s.day() == args.day)
+        .ok_or_else(|| format!("There is no solution for day {}", args.day))?;
+
+    let file_path = match &args.input {
+        Some(path) => path.clone(),
+        None => default_input(args.day, args.part),
+    };
+    let input = std::fs::read_to_string(&file_path)
+        .map_err(|err| format!("Error reading file '{}': {err}", file_path.display()))?;
+
+    let answer = match args.part {
+        1 => solution.part1(&input)?,
+        _ => solution.part2(&input)?,
+    };
+    println!("Day {} part {} = {answer}", args.day, args.part);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let run_args = match parse_args(&args) {
+        Ok(run_args) => run_args,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&run_args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
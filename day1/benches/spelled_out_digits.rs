@@ -0,0 +1,109 @@
+//! Compares the automaton that finds spelled-out digits with the character-by-character scan it replaced, and with
+//! the automaton spread across threads, on a generated document of `DAY1_BENCH_MB` megabytes (256 by default).
+//!
+//! Run with `cargo bench -p day1`.
+
+use day1::trebuchet_calibration::{
+    get_calibration_sum_from_spelled_out_digits,
+    get_calibration_sum_from_spelled_out_digits_parallel, to_decimal_digit, Lexicon, Rule,
+};
+use solution::rng::XorShift;
+use std::time::{Duration, Instant};
+
+/// Generates lines of filler letters with the occasional digit or word from the lexicon.
+fn generate_document(size: usize, lexicon: &Lexicon, rng: &mut XorShift) -> String {
+    let mut document = String::with_capacity(size + 128);
+    while document.len() < size {
+        let length = 5 + rng.below(60);
+        let line_start = document.len();
+        while document.len() - line_start < length {
+            match rng.below(20) {
+                0 => document.push(char::from(b'1' + rng.below(9) as u8)),
+                1 | 2 => document.push_str(&lexicon.words()[rng.below(lexicon.words().len())].0),
+                _ => document.push(char::from(b'a' + rng.below(26) as u8)),
+            }
+        }
+        document.push('\n');
+    }
+    document
+}
+
+/// The scan the automaton replaced: walk the characters from one end of the line and try every word at each one.
+fn get_digit(
+    line: &str,
+    mut chars: impl Iterator<Item = (usize, char)>,
+    lexicon: &Lexicon,
+) -> Option<u32> {
+    chars.find_map(|(i, c)| {
+        to_decimal_digit(c)
+            .filter(|&digit| digit != 0 || lexicon.spells_zero())
+            .or_else(|| lexicon.word_at(line, i).map(|(_, digit)| digit))
+    })
+}
+
+fn get_calibration_sum_by_scanning(calibration_document: &str, lexicon: &Lexicon) -> u64 {
+    calibration_document
+        .lines()
+        .filter_map(|line| {
+            let first_digit = get_digit(line, line.char_indices(), lexicon)?;
+            let last_digit = get_digit(line, line.char_indices().rev(), lexicon)?;
+            Some(u64::from(first_digit * 10 + last_digit))
+        })
+        .sum()
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+fn main() {
+    let megabytes = std::env::var("DAY1_BENCH_MB")
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .unwrap_or(256);
+    let lexicon = Lexicon::english();
+    let document = generate_document(megabytes << 20, &lexicon, &mut XorShift::new(0x2023_1201));
+    let size = document.len() as f64 / f64::from(1 << 20);
+    println!(
+        "document: {size:.0} MiB, {} lines",
+        document.lines().count()
+    );
+
+    let (expected, scanning) = time(|| get_calibration_sum_by_scanning(&document, &lexicon));
+    let (actual, automaton) =
+        time(|| get_calibration_sum_from_spelled_out_digits(&document, &lexicon));
+    assert_eq!(actual, Ok(expected), "the automaton and the scan disagree");
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let (parallel_sum, parallel) = time(|| {
+        get_calibration_sum_from_spelled_out_digits_parallel(
+            document.as_bytes(),
+            &lexicon,
+            &Rule::default(),
+            threads,
+        )
+    });
+    assert_eq!(
+        parallel_sum.ok(),
+        Some(expected),
+        "the threads and the scan disagree"
+    );
+
+    for (name, elapsed) in [
+        ("scanning", scanning),
+        ("automaton", automaton),
+        ("parallel", parallel),
+    ] {
+        println!(
+            "{name:>10}: {:>8.1} ms, {:>8.1} MiB/s",
+            elapsed.as_secs_f64() * 1000.0,
+            size / elapsed.as_secs_f64()
+        );
+    }
+    println!(
+        "   speedup: {:.2}x, {:.2}x on {threads} threads",
+        scanning.as_secs_f64() / automaton.as_secs_f64(),
+        scanning.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
@@ -1,12 +1,44 @@
-mod trebuchet_calibration;
+use day1::trebuchet_calibration;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let file_path = args
         .get(1)
         .expect("Please supply an input file as the first argument");
-    let input = std::fs::read_to_string(&args[1])
+    let input = std::fs::read(&args[1])
         .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"));
-    let value = trebuchet_calibration::get_calibration_sum(&input);
-    println!("trebuchet calibration value = {value}");
+
+    // `--strict` fails with every line that can't be read instead of skipping it.
+    let value = if args.iter().any(|arg| arg == "--strict") {
+        trebuchet_calibration::get_calibration_sum_strict(&input).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1)
+        })
+    } else {
+        trebuchet_calibration::get_calibration_sum(to_text(&input, file_path)).unwrap_or_else(
+            |err| {
+                eprintln!("{err}");
+                std::process::exit(1)
+            },
+        )
+    };
+
+    // `--report csv` or `--report json` prints how each line's value was derived instead of the total.
+    match args.iter().position(|arg| arg == "--report") {
+        Some(i) => {
+            let format = args
+                .get(i + 1)
+                .expect("Please supply a format after --report")
+                .parse::<trebuchet_calibration::ReportFormat>()
+                .unwrap_or_else(|err| panic!("{err}"));
+            let report = trebuchet_calibration::get_calibration_report(to_text(&input, file_path));
+            print!("{}", report.render(format));
+        }
+        None => println!("trebuchet calibration value = {value}"),
+    }
+}
+
+fn to_text<'a>(input: &'a [u8], file_path: &str) -> &'a str {
+    std::str::from_utf8(input)
+        .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"))
 }
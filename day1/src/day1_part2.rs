@@ -1,12 +1,76 @@
-mod trebuchet_calibration;
+use day1::trebuchet_calibration;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let file_path = args
         .get(1)
         .expect("Please supply an input file as the first argument");
-    let input = std::fs::read_to_string(&args[1])
+    let input = std::fs::read(&args[1])
         .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"));
-    let value = trebuchet_calibration::get_calibration_sum_from_spelled_out_digits(&input);
-    println!("trebuchet calibration value = {value}");
+
+    // The words for digits default to English, e.g. `--lexicon german` or `--lexicon path/to/words.txt`.
+    let lexicon = match args.iter().position(|arg| arg == "--lexicon") {
+        Some(i) => args
+            .get(i + 1)
+            .expect("Please supply a lexicon name or file after --lexicon")
+            .parse::<trebuchet_calibration::Lexicon>()
+            .unwrap_or_else(|err| panic!("{err}")),
+        None => trebuchet_calibration::Lexicon::default(),
+    };
+
+    // `--strict` fails with every line that can't be read instead of skipping it.
+    let value = if args.iter().any(|arg| arg == "--strict") {
+        trebuchet_calibration::get_calibration_sum_from_spelled_out_digits_strict(&input, &lexicon)
+            .unwrap_or_else(|err| {
+                eprintln!("{err}");
+                std::process::exit(1)
+            })
+    } else if let Some(i) = args.iter().position(|arg| arg == "--fuzzy") {
+        // `--fuzzy 1` lists misspelled words within one edit of the lexicon, and uses them on lines with no digits.
+        let max_distance = args
+            .get(i + 1)
+            .expect("Please supply an edit distance after --fuzzy")
+            .parse::<usize>()
+            .unwrap_or_else(|err| panic!("Error parsing the edit distance: {err:?}"));
+        let input = to_text(&input, file_path);
+        for suggestion in trebuchet_calibration::suggest_repairs(input, &lexicon, max_distance) {
+            eprintln!("{suggestion}");
+        }
+        trebuchet_calibration::get_calibration_sum_with_repairs(input, &lexicon, max_distance)
+            .unwrap_or_else(|err| {
+                eprintln!("{err}");
+                std::process::exit(1)
+            })
+    } else {
+        trebuchet_calibration::get_calibration_sum_from_spelled_out_digits(
+            to_text(&input, file_path),
+            &lexicon,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1)
+        })
+    };
+
+    // `--report csv` or `--report json` prints how each line's value was derived instead of the total.
+    match args.iter().position(|arg| arg == "--report") {
+        Some(i) => {
+            let format = args
+                .get(i + 1)
+                .expect("Please supply a format after --report")
+                .parse::<trebuchet_calibration::ReportFormat>()
+                .unwrap_or_else(|err| panic!("{err}"));
+            let report = trebuchet_calibration::get_calibration_report_from_spelled_out_digits(
+                to_text(&input, file_path),
+                &lexicon,
+            );
+            print!("{}", report.render(format));
+        }
+        None => println!("trebuchet calibration value = {value}"),
+    }
+}
+
+fn to_text<'a>(input: &'a [u8], file_path: &str) -> &'a str {
+    std::str::from_utf8(input)
+        .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"))
 }
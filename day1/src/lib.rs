@@ -0,0 +1,28 @@
+//! Day 1: Trebuchet?!
+
+use solution::{Answer, Solution};
+use std::error::Error;
+
+pub mod trebuchet_calibration;
+
+/// Solution for day 1.
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn day(&self) -> u32 {
+        1
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(trebuchet_calibration::get_calibration_sum(input)?))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(
+            trebuchet_calibration::get_calibration_sum_from_spelled_out_digits(
+                input,
+                &trebuchet_calibration::Lexicon::english(),
+            )?,
+        ))
+    }
+}
@@ -1,3 +1,37 @@
+//! Recovery of the calibration values from the trebuchet's calibration document.
+
+mod fuzzy;
+mod generator;
+mod lexicon;
+mod matcher;
+mod report;
+mod rules;
+mod stream;
+mod strict;
+
+pub use fuzzy::{get_calibration_sum_with_repairs, suggest_repairs, Suggestion};
+pub use generator::{DocumentGenerator, GeneratedDocument};
+pub use lexicon::{Lexicon, LexiconError, BUILT_IN_LEXICONS};
+pub use matcher::{DigitMatch, DigitMatcher, MatchKind};
+pub use report::{
+    get_calibration_report, get_calibration_report_from_spelled_out_digits, CalibrationReport,
+    LineReport, ReportFormat, ReportedToken,
+};
+pub use rules::{
+    get_calibration_sum_from_spelled_out_digits_with_rule, get_calibration_sum_with_rule,
+    CalibrationRule, OverflowError, Rule,
+};
+pub use stream::{
+    get_calibration_sum_from_reader, get_calibration_sum_from_spelled_out_digits_from_reader,
+    get_calibration_sum_from_spelled_out_digits_parallel, get_calibration_sum_parallel,
+};
+pub use strict::{
+    get_calibration_sum_from_spelled_out_digits_strict, get_calibration_sum_strict,
+    CalibrationError, LineDiagnostic, LineProblem,
+};
+
+// Only constants and functions follow the tests, which clippy would otherwise flag.
+#[allow(clippy::items_after_test_module)]
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -13,7 +47,16 @@ treb7uchet
 
         let result = get_calibration_sum(input);
 
-        assert_eq!(result, 142);
+        assert_eq!(result, Ok(142));
+    }
+
+    #[test]
+    fn get_calibration_sum_reads_decimal_digits_of_any_script() {
+        let input = "ab\u{FF17}c\u{663}\nnaïve 1 🎉\n\u{660}x\u{669}\n\u{967}\u{9EE}";
+
+        let result = get_calibration_sum(input);
+
+        assert_eq!(result, Ok(73 + 11 + 9 + 18));
     }
 
     #[test]
@@ -28,107 +71,192 @@ zoneight234
 7pqrstsixteen
         ";
 
-        let result = get_calibration_sum_from_spelled_out_digits(input);
+        let result = get_calibration_sum_from_spelled_out_digits(input, &Lexicon::english());
 
-        assert_eq!(result, 281);
+        assert_eq!(result, Ok(281));
     }
-}
 
-enum Position {
-    Beginning,
-    End,
-}
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_skips_zeros_with_the_english_words() {
+        let input = "0two0\nx\u{FF10}7\u{660}\n10";
+
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(input, &Lexicon::english()),
+            Ok(22 + 77 + 11)
+        );
+        assert_eq!(get_calibration_sum(input), Ok(10));
+    }
 
-fn get_digit(line: &str, starting_from: Position) -> &str {
-    let num_letters = line.len();
-    let mut index = Some(match starting_from {
-        Position::Beginning => 0,
-        Position::End => std::cmp::max(0, num_letters - 1),
-    });
-    let get_next_index = |i: usize| match starting_from {
-        Position::Beginning => {
-            if i == num_letters {
-                None
-            } else {
-                Some(i + 1)
-            }
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_counts_zeros_when_the_lexicon_spells_zero() {
+        let lexicon = Lexicon::new([("zero", 0), ("five", 5)]);
+        for (spelled, written) in [("zero5", "05"), ("fivezero", "50"), ("xzerox", "x0x")] {
+            assert_eq!(
+                get_calibration_sum_from_spelled_out_digits(spelled, &lexicon),
+                get_calibration_sum_from_spelled_out_digits(written, &lexicon),
+                "{spelled} and {written}"
+            );
         }
-        Position::End => match i {
-            0 => None,
-            _ => Some(i - 1),
-        },
-    };
-
-    while index.is_some() {
-        let i = index.unwrap();
-        let current_char = &line[i..std::cmp::min(num_letters, i + 1)];
-        match current_char {
-            "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => return current_char,
-            _ => {}
-        };
-
-        let three_letter_word = &line[i..std::cmp::min(num_letters, i + 3)];
-        match three_letter_word {
-            "one" => return "1",
-            "two" => return "2",
-            "six" => return "6",
-            _ => {}
-        };
-
-        let four_letter_word = &line[i..std::cmp::min(num_letters, i + 4)];
-        match four_letter_word {
-            "four" => return "4",
-            "five" => return "5",
-            "nine" => return "9",
-            _ => {}
-        };
-
-        let five_letter_word = &line[i..std::cmp::min(num_letters, i + 5)];
-        match five_letter_word {
-            "three" => return "3",
-            "seven" => return "7",
-            "eight" => return "8",
-            _ => {}
-        };
-
-        index = get_next_index(i)
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits("null5\n05\n7\u{FF10}", &Lexicon::german()),
+            Ok(5 + 5 + 70)
+        );
     }
 
-    ""
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_uses_the_words_of_the_lexicon() {
+        let german = "xsiebenzwei\nachtundneunzig";
+        let spanish = "cuatro8tres\nsiete";
+        let french = "1quatreneuf\nxhuitx";
+
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(german, &Lexicon::german()),
+            Ok(72 + 89)
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(spanish, &Lexicon::spanish()),
+            Ok(43 + 77)
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(french, &Lexicon::french()),
+            Ok(19 + 88)
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(german, &Lexicon::english()),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_accepts_words_of_any_length() {
+        let lexicon = Lexicon::new([("zero", 0), ("seventeenish", 7)]);
+
+        let result = get_calibration_sum_from_spelled_out_digits("zero5seventeenish", &lexicon);
+
+        assert_eq!(result, Ok(7));
+    }
+
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_walks_characters_not_bytes() {
+        let input = "café🎉two\u{663}\n\n🎉\nü\nñ\u{FF18}ñ\nthreé";
+
+        let result = get_calibration_sum_from_spelled_out_digits(input, &Lexicon::english());
+
+        assert_eq!(result, Ok(23 + 88));
+    }
+
+    #[test]
+    fn get_calibration_sum_from_spelled_out_digits_matches_words_with_accents() {
+        let german = "fünfxsieben\nüberdreiß";
+        let french = "zéro7\nneuf🎉\u{FF12}";
+
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(german, &Lexicon::german()),
+            Ok(57 + 33)
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits(french, &Lexicon::french()),
+            Ok(7 + 92)
+        );
+    }
+}
+
+/// The zero of every run of Unicode decimal digits (general category Nd) as of Unicode 15.0. Each run holds the digits
+/// zero to nine in order, e.g. `'\u{FF10}'` to `'\u{FF19}'` are the full-width digits `０` to `９`.
+const DECIMAL_DIGIT_ZEROS: &[char] = &[
+    '\u{30}',
+    '\u{660}',
+    '\u{6F0}',
+    '\u{7C0}',
+    '\u{966}',
+    '\u{9E6}',
+    '\u{A66}',
+    '\u{AE6}',
+    '\u{B66}',
+    '\u{BE6}',
+    '\u{C66}',
+    '\u{CE6}',
+    '\u{D66}',
+    '\u{DE6}',
+    '\u{E50}',
+    '\u{ED0}',
+    '\u{F20}',
+    '\u{1040}',
+    '\u{1090}',
+    '\u{17E0}',
+    '\u{1810}',
+    '\u{1946}',
+    '\u{19D0}',
+    '\u{1A80}',
+    '\u{1A90}',
+    '\u{1B50}',
+    '\u{1BB0}',
+    '\u{1C40}',
+    '\u{1C50}',
+    '\u{A620}',
+    '\u{A8D0}',
+    '\u{A900}',
+    '\u{A9D0}',
+    '\u{A9F0}',
+    '\u{AA50}',
+    '\u{ABF0}',
+    '\u{FF10}',
+    '\u{104A0}',
+    '\u{10D30}',
+    '\u{11066}',
+    '\u{110F0}',
+    '\u{11136}',
+    '\u{111D0}',
+    '\u{112F0}',
+    '\u{11450}',
+    '\u{114D0}',
+    '\u{11650}',
+    '\u{116C0}',
+    '\u{11730}',
+    '\u{118E0}',
+    '\u{11950}',
+    '\u{11C50}',
+    '\u{11D50}',
+    '\u{11DA0}',
+    '\u{11F50}',
+    '\u{16A60}',
+    '\u{16AC0}',
+    '\u{16B50}',
+    '\u{1D7CE}',
+    '\u{1D7D8}',
+    '\u{1D7E2}',
+    '\u{1D7EC}',
+    '\u{1D7F6}',
+    '\u{1E140}',
+    '\u{1E2F0}',
+    '\u{1E4F0}',
+    '\u{1E950}',
+    '\u{1FBF0}',
+];
+
+/// Returns the value of a decimal digit in any script, e.g. `7`, full-width `７` or Arabic-Indic `٧`.
+pub fn to_decimal_digit(c: char) -> Option<u32> {
+    let c = u32::from(c);
+    DECIMAL_DIGIT_ZEROS
+        .iter()
+        .map(|&zero| u32::from(zero))
+        .find(|zero| (*zero..*zero + 10).contains(&c))
+        .map(|zero| c - zero)
 }
 
-pub fn get_calibration_sum_from_spelled_out_digits(calibration_document: &str) -> i32 {
-    let transformed_calibration_document = calibration_document
-        .lines()
-        .map(|line| {
-            let first_digit = get_digit(line, Position::Beginning);
-            let last_digit = get_digit(line, Position::End);
-            format!("{first_digit}{last_digit}")
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-    get_calibration_sum(&transformed_calibration_document)
+/// Sums the calibration values of the document, where digits may also be spelled out with the words of the lexicon.
+pub fn get_calibration_sum_from_spelled_out_digits(
+    calibration_document: &str,
+    lexicon: &Lexicon,
+) -> Result<u64, OverflowError> {
+    get_calibration_sum_from_spelled_out_digits_with_rule(
+        calibration_document,
+        lexicon,
+        &Rule::default(),
+    )
 }
 
-pub fn get_calibration_sum(calibration_document: &str) -> i32 {
-    calibration_document
-        .lines()
-        .filter_map(|line| {
-            if line.is_empty() {
-                return None;
-            }
-
-            let digits = line
-                .chars()
-                .filter_map(|c| match c {
-                    '0'..='9' => format!("{c}").parse::<i32>().ok(),
-                    _ => None,
-                })
-                .collect::<Vec<_>>();
-            if digits.is_empty() {
-                return None;
-            }
-            Some(digits.first().unwrap() * 10 + digits.last().unwrap())
-        })
-        .sum()
+/// Sums the calibration values of the document: the first and last digit of each line, as a two-digit number.
+pub fn get_calibration_sum(calibration_document: &str) -> Result<u64, OverflowError> {
+    get_calibration_sum_with_rule(calibration_document, &Rule::default())
 }
@@ -0,0 +1,246 @@
+//! Suggestions for misspelled digit words, such as `sevn` for `seven`, found by edit distance.
+
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use super::rules::{checked_sum, spelled_out_line_value};
+use super::{DigitMatcher, Lexicon, MatchKind, OverflowError, Rule};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggested(suggestions: &[Suggestion]) -> Vec<(usize, &str, &str, usize)> {
+        suggestions
+            .iter()
+            .map(|s| (s.line, s.text.as_str(), s.word.as_str(), s.distance))
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_suggests_words_within_the_edit_distance() {
+        let input = "xnin\nsevnx\n0ne\nfour";
+
+        let suggestions = suggest_repairs(input, &Lexicon::english(), 1);
+
+        assert_eq!(
+            suggested(&suggestions),
+            vec![
+                (1, "nin", "nine", 1),
+                (2, "sevn", "seven", 1),
+                (3, "0ne", "one", 1)
+            ]
+        );
+        assert_eq!(suggestions[1].digit, 7);
+        assert_eq!(suggestions[1].bytes, 0..4);
+        assert!((suggestions[1].confidence - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fuzzy_needs_at_least_half_of_a_word_to_match() {
+        let suggestions = suggest_repairs("thre\nthr\nth", &Lexicon::english(), 3);
+
+        assert_eq!(
+            suggested(&suggestions),
+            vec![(1, "thre", "three", 1), (2, "thr", "three", 2)]
+        );
+    }
+
+    #[test]
+    fn fuzzy_ignores_words_that_are_spelled_correctly() {
+        let suggestions = suggest_repairs("seven\n7eight", &Lexicon::english(), 2);
+
+        assert!(suggestions.is_empty(), "{suggestions:?}");
+    }
+
+    #[test]
+    fn fuzzy_repairs_only_lines_without_digits() {
+        let input = "xnin\n3sevn\nfor and sx";
+        let lexicon = Lexicon::english();
+
+        assert_eq!(
+            get_calibration_sum_with_repairs(input, &lexicon, 1),
+            Ok(99 + 33 + 46)
+        );
+        assert_eq!(get_calibration_sum_with_repairs(input, &lexicon, 0), Ok(33));
+        assert_eq!(
+            get_calibration_sum_with_repairs("x0ne", &lexicon, 1),
+            Ok(11)
+        );
+    }
+
+    #[test]
+    fn fuzzy_edit_distance_counts_insertions_deletions_and_substitutions() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+
+        assert_eq!(edit_distance(&chars("seven"), &chars("sevn")), 1);
+        assert_eq!(edit_distance(&chars("one"), &chars("0ne")), 1);
+        assert_eq!(edit_distance(&chars("nine"), &chars("ninee")), 1);
+        assert_eq!(edit_distance(&chars("four"), &chars("fuor")), 2);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    }
+}
+
+/// A near-miss to a word of the lexicon, and the digit it was probably meant to be.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// The 1-based line number.
+    pub line: usize,
+    /// Where the near-miss is in the line, in bytes.
+    pub bytes: Range<usize>,
+    /// The text of the near-miss, e.g. `sevn`.
+    pub text: String,
+    /// The lexicon word it is closest to, e.g. `seven`.
+    pub word: String,
+    /// The digit of the word.
+    pub digit: u32,
+    /// The number of characters inserted, deleted or substituted to get from the word to the text.
+    pub distance: usize,
+    /// How sure the suggestion is, from 0 to 1: the fraction of the word's characters that didn't need an edit.
+    pub confidence: f64,
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: '{}' may be '{}' ({}), confidence {:.2}",
+            self.line, self.text, self.word, self.digit, self.confidence
+        )
+    }
+}
+
+/// Finds near-misses to the words of the lexicon that are within `max_distance` edits. At least half of a word's
+/// characters must be right for it to be suggested, and text that already spells a word correctly is never
+/// suggested as another.
+pub fn suggest_repairs(
+    calibration_document: &str,
+    lexicon: &Lexicon,
+    max_distance: usize,
+) -> Vec<Suggestion> {
+    let fuzzy = FuzzyLexicon::new(lexicon, max_distance);
+    calibration_document
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| fuzzy.suggestions(i + 1, line))
+        .collect()
+}
+
+/// Sums the calibration values like [`super::get_calibration_sum_from_spelled_out_digits`], but lines without any
+/// digit take their first and last digit from the suggested repairs instead of being skipped.
+pub fn get_calibration_sum_with_repairs(
+    calibration_document: &str,
+    lexicon: &Lexicon,
+    max_distance: usize,
+) -> Result<u64, OverflowError> {
+    let matcher = DigitMatcher::new(lexicon);
+    let fuzzy = FuzzyLexicon::new(lexicon, max_distance);
+    checked_sum(
+        calibration_document
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                spelled_out_line_value(line, &matcher, &Rule::default()).or_else(|| {
+                    let suggestions = fuzzy.suggestions(i + 1, line);
+                    let (first, last) = (suggestions.first()?, suggestions.last()?);
+                    Some(u64::from(first.digit * 10 + last.digit))
+                })
+            }),
+    )
+}
+
+struct FuzzyLexicon {
+    /// The words with their characters, digits and the most edits allowed for each.
+    words: Vec<(Vec<char>, String, u32, usize)>,
+    matcher: DigitMatcher,
+}
+
+impl FuzzyLexicon {
+    fn new(lexicon: &Lexicon, max_distance: usize) -> Self {
+        let words = lexicon
+            .words()
+            .iter()
+            .map(|(word, digit)| {
+                let chars = word.chars().collect::<Vec<_>>();
+                let allowed = max_distance.min(chars.len().saturating_sub(1) / 2);
+                (chars, word.clone(), *digit, allowed)
+            })
+            .collect();
+        Self {
+            words,
+            matcher: DigitMatcher::new(lexicon),
+        }
+    }
+
+    /// The best non-overlapping suggestions for the line, in order.
+    fn suggestions(&self, line_number: usize, line: &str) -> Vec<Suggestion> {
+        let exact_words = self
+            .matcher
+            .find_iter(line)
+            .filter(|m| m.kind == MatchKind::Word)
+            .map(|m| m.start..m.end)
+            .collect::<Vec<_>>();
+        let overlaps = |a: &Range<usize>, b: &Range<usize>| a.start < b.end && b.start < a.end;
+
+        let (offsets, chars): (Vec<usize>, Vec<char>) = line.char_indices().unzip();
+        let byte_at = |i: usize| offsets.get(i).copied().unwrap_or(line.len());
+
+        let mut candidates = vec![];
+        for (word_chars, word, digit, allowed) in &self.words {
+            if *allowed == 0 {
+                continue;
+            }
+            let shortest = word_chars.len() - allowed;
+            let longest = word_chars.len() + allowed;
+            for start in 0..chars.len() {
+                for end in start + shortest..=(start + longest).min(chars.len()) {
+                    let distance = edit_distance(word_chars, &chars[start..end]);
+                    let bytes = byte_at(start)..byte_at(end);
+                    if distance == 0
+                        || distance > *allowed
+                        || exact_words.iter().any(|exact| overlaps(exact, &bytes))
+                    {
+                        continue;
+                    }
+                    candidates.push(Suggestion {
+                        line: line_number,
+                        text: line[bytes.clone()].to_owned(),
+                        bytes,
+                        word: word.clone(),
+                        digit: *digit,
+                        distance,
+                        confidence: 1.0 - distance as f64 / word_chars.len() as f64,
+                    });
+                }
+            }
+        }
+
+        // The closest candidates win, then the earliest, then the longest.
+        candidates.sort_by(|a, b| {
+            (a.distance, a.bytes.start, b.bytes.end).cmp(&(b.distance, b.bytes.start, a.bytes.end))
+        });
+        let mut chosen: Vec<Suggestion> = vec![];
+        for candidate in candidates {
+            if !chosen.iter().any(|c| overlaps(&c.bytes, &candidate.bytes)) {
+                chosen.push(candidate);
+            }
+        }
+        chosen.sort_by_key(|s| s.bytes.start);
+        chosen
+    }
+}
+
+/// The Levenshtein distance between two strings of characters.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,335 @@
+//! Random calibration documents with known answers, for testing calibration at scale.
+
+use std::collections::HashSet;
+
+use solution::rng::XorShift;
+
+use super::Lexicon;
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        get_calibration_sum, get_calibration_sum_from_reader,
+        get_calibration_sum_from_spelled_out_digits,
+        get_calibration_sum_from_spelled_out_digits_parallel, Rule,
+    };
+    use super::*;
+
+    fn generators() -> Vec<DocumentGenerator> {
+        (0..40)
+            .map(|seed| {
+                let density = (seed % 5) as f64 / 10.0;
+                let mut generator = DocumentGenerator::new(seed);
+                generator
+                    .lines(1 + (seed as usize * 7) % 60)
+                    .line_length(seed as usize % 4, 4 + seed as usize % 70)
+                    .digit_density(density)
+                    .word_density(0.4 - density)
+                    .trap_density(0.05 + density / 2.0)
+                    .digit_free_lines((seed % 3) as f64 / 4.0);
+                generator
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generated_documents_have_the_expected_sums() {
+        let lexicon = Lexicon::english();
+        for generator in generators() {
+            let document = generator.generate();
+
+            assert_eq!(
+                get_calibration_sum(&document.text),
+                Ok(document.part1_sum),
+                "part 1 of {generator:?}:\n{}",
+                document.text
+            );
+            assert_eq!(
+                get_calibration_sum_from_spelled_out_digits(&document.text, &lexicon),
+                Ok(document.part2_sum),
+                "part 2 of {generator:?}:\n{}",
+                document.text
+            );
+        }
+    }
+
+    #[test]
+    fn generated_documents_have_the_expected_sums_when_streamed() {
+        let lexicon = Lexicon::english();
+        for generator in generators() {
+            let document = generator.generate();
+
+            let part1 = get_calibration_sum_from_reader(document.text.as_bytes(), &Rule::default());
+            let part2 = get_calibration_sum_from_spelled_out_digits_parallel(
+                document.text.as_bytes(),
+                &lexicon,
+                &Rule::default(),
+                3,
+            );
+
+            assert_eq!(part1.ok(), Some(document.part1_sum));
+            assert_eq!(part2.ok(), Some(document.part2_sum));
+        }
+    }
+
+    #[test]
+    fn generated_documents_depend_only_on_the_seed() {
+        let mut generator = DocumentGenerator::new(7);
+        generator.lines(20);
+
+        assert_eq!(generator.generate(), generator.generate());
+        assert_ne!(
+            generator.generate(),
+            DocumentGenerator::new(8).lines(20).generate()
+        );
+    }
+
+    #[test]
+    fn generated_documents_follow_the_options() {
+        let document = DocumentGenerator::new(1)
+            .lines(50)
+            .line_length(10, 20)
+            .digit_density(0.0)
+            .trap_density(1.0)
+            .generate();
+
+        let lines = document.text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 50);
+        for line in lines {
+            let length = line.chars().count();
+            assert!((10..=20 + 9).contains(&length), "{line:?} is {length} long");
+            assert!(!line.chars().any(|c| c.is_ascii_digit()), "{line:?}");
+        }
+        assert_eq!(document.part1_sum, 0);
+        assert!(document.part2_sum > 0);
+    }
+
+    #[test]
+    fn generated_traps_overlap_by_one_letter() {
+        let traps = traps(&Lexicon::english());
+
+        for trap in [
+            "oneight",
+            "twone",
+            "threeight",
+            "eightwo",
+            "eighthree",
+            "nineight",
+        ] {
+            assert!(traps.iter().any(|t| t.text == trap), "{trap} is missing");
+        }
+        let twone = traps.iter().find(|t| t.text == "twone");
+        assert_eq!(twone.map(|t| (t.first, t.last)), Some((2, 1)));
+    }
+}
+
+/// A generated calibration document, with the sums it should calibrate to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedDocument {
+    /// The text of the document.
+    pub text: String,
+    /// The sum of the calibration values from written digits only, as in part 1.
+    pub part1_sum: u64,
+    /// The sum of the calibration values from written and spelled-out digits, as in part 2.
+    pub part2_sum: u64,
+}
+
+/// Generates random calibration documents from a seed.
+///
+/// Lines are built from runs of filler letters that can't spell any word of the lexicon, separating written digits,
+/// words, and traps where two words share a letter, such as `oneight`.
+#[derive(Debug, Clone)]
+pub struct DocumentGenerator {
+    seed: u64,
+    lines: usize,
+    min_length: usize,
+    max_length: usize,
+    digit_density: f64,
+    word_density: f64,
+    trap_density: f64,
+    digit_free_lines: f64,
+    lexicon: Lexicon,
+}
+
+/// Two words that overlap by one letter, e.g. `oneight`, and the digits that count when it comes first or last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Trap {
+    text: String,
+    first: u32,
+    last: u32,
+}
+
+/// A piece of a generated line.
+enum Token {
+    Filler,
+    Digit(u32),
+    Word(usize),
+    Trap(usize),
+}
+
+impl DocumentGenerator {
+    /// Creates a generator of 100 lines of 1 to 40 characters from the seed, using English words.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            lines: 100,
+            min_length: 1,
+            max_length: 40,
+            digit_density: 0.1,
+            word_density: 0.1,
+            trap_density: 0.02,
+            digit_free_lines: 0.05,
+            lexicon: Lexicon::english(),
+        }
+    }
+
+    /// Sets the number of lines.
+    pub fn lines(&mut self, lines: usize) -> &mut Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Sets the range of line lengths, in characters. A line can run over the maximum by the length of its last word.
+    pub fn line_length(&mut self, min: usize, max: usize) -> &mut Self {
+        self.min_length = min;
+        self.max_length = max.max(min);
+        self
+    }
+
+    /// Sets how often a piece of a line is a written digit, from 0 to 1.
+    pub fn digit_density(&mut self, density: f64) -> &mut Self {
+        self.digit_density = density;
+        self
+    }
+
+    /// Sets how often a piece of a line is a spelled-out digit, from 0 to 1.
+    pub fn word_density(&mut self, density: f64) -> &mut Self {
+        self.word_density = density;
+        self
+    }
+
+    /// Sets how often a piece of a line is two words overlapping by a letter, such as `twone`, from 0 to 1.
+    pub fn trap_density(&mut self, density: f64) -> &mut Self {
+        self.trap_density = density;
+        self
+    }
+
+    /// Sets the fraction of lines that are only filler, with no digits or words at all.
+    pub fn digit_free_lines(&mut self, fraction: f64) -> &mut Self {
+        self.digit_free_lines = fraction;
+        self
+    }
+
+    /// Sets the lexicon whose words are spelled out. The expected sums assume no word of the lexicon contains another.
+    pub fn lexicon(&mut self, lexicon: Lexicon) -> &mut Self {
+        self.lexicon = lexicon;
+        self
+    }
+
+    /// Generates a document. The same settings always generate the same document.
+    pub fn generate(&self) -> GeneratedDocument {
+        let mut rng = XorShift::new(self.seed);
+        let words = self.lexicon.words();
+        let traps = traps(&self.lexicon);
+        let filler = filler(&self.lexicon);
+
+        let mut document = GeneratedDocument {
+            text: String::new(),
+            part1_sum: 0,
+            part2_sum: 0,
+        };
+        for _ in 0..self.lines {
+            let length = self.min_length + rng.below(self.max_length - self.min_length + 1);
+            let digit_free = rng.chance(self.digit_free_lines);
+
+            let mut line = String::new();
+            let mut digits: Vec<u32> = vec![];
+            let mut spelled: Vec<(u32, u32)> = vec![];
+            let mut last_was_filler = true;
+            while line.chars().count() < length {
+                let roll = rng.fraction();
+                let token = if digit_free || !last_was_filler {
+                    // Words are always separated by filler, so they can't run together into other words.
+                    Token::Filler
+                } else if roll < self.digit_density {
+                    Token::Digit(1 + rng.below(9) as u32)
+                } else if roll < self.digit_density + self.word_density && !words.is_empty() {
+                    Token::Word(rng.below(words.len()))
+                } else if roll < self.digit_density + self.word_density + self.trap_density
+                    && !traps.is_empty()
+                {
+                    Token::Trap(rng.below(traps.len()))
+                } else {
+                    Token::Filler
+                };
+
+                last_was_filler = matches!(token, Token::Filler);
+                match token {
+                    Token::Filler => line.push(filler[rng.below(filler.len())]),
+                    Token::Digit(digit) => {
+                        line.push(char::from(b'0' + digit as u8));
+                        digits.push(digit);
+                        spelled.push((digit, digit));
+                    }
+                    Token::Word(i) => {
+                        let (word, digit) = &words[i];
+                        line.push_str(word);
+                        spelled.push((*digit, *digit));
+                    }
+                    Token::Trap(i) => {
+                        line.push_str(&traps[i].text);
+                        spelled.push((traps[i].first, traps[i].last));
+                    }
+                }
+            }
+
+            if let (Some(first), Some(last)) = (digits.first(), digits.last()) {
+                document.part1_sum += u64::from(first * 10 + last);
+            }
+            if let (Some(first), Some(last)) = (spelled.first(), spelled.last()) {
+                document.part2_sum += u64::from(first.0 * 10 + last.1);
+            }
+            document.text.push_str(&line);
+            document.text.push('\n');
+        }
+        document
+    }
+}
+
+/// Every pair of words where the last letter of one is the first letter of the other, joined on that letter.
+fn traps(lexicon: &Lexicon) -> Vec<Trap> {
+    let mut traps = vec![];
+    for (first_word, first) in lexicon.words() {
+        for (last_word, last) in lexicon.words() {
+            let Some(shared) = first_word.chars().last() else {
+                continue;
+            };
+            if let Some(rest) = last_word.strip_prefix(shared) {
+                traps.push(Trap {
+                    text: format!("{first_word}{rest}"),
+                    first: *first,
+                    last: *last,
+                });
+            }
+        }
+    }
+    traps
+}
+
+/// The letters that appear in no word of the lexicon, so filler can never spell a word or a digit.
+fn filler(lexicon: &Lexicon) -> Vec<char> {
+    let used = lexicon
+        .words()
+        .iter()
+        .flat_map(|(word, _)| word.chars())
+        .collect::<HashSet<_>>();
+    let filler = ('a'..='z')
+        .chain("-_.:;!?".chars())
+        .filter(|c| !used.contains(c))
+        .collect::<Vec<_>>();
+    if filler.is_empty() {
+        vec![' ']
+    } else {
+        filler
+    }
+}
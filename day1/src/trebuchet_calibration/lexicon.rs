@@ -0,0 +1,318 @@
+//! Tables of the words that spell out digits in calibration documents.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexicon_parses_one_word_and_digit_per_line() {
+        let text = r"
+# Dutch
+nul 0
+een 1
+
+twee 2
+";
+
+        let lexicon = Lexicon::try_from(text).expect("lexicon should parse");
+
+        assert_eq!(
+            lexicon.words(),
+            &[
+                ("twee".to_owned(), 2),
+                ("nul".to_owned(), 0),
+                ("een".to_owned(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn lexicon_reports_the_line_of_a_bad_entry() {
+        let result = Lexicon::try_from("one 1\ntwo\n");
+        assert_eq!(
+            result,
+            Err(LexiconError::Line {
+                line: 2,
+                text: "two".to_owned()
+            })
+        );
+
+        let result = Lexicon::try_from("one 1\ntwo 12\n");
+        assert_eq!(
+            result,
+            Err(LexiconError::Line {
+                line: 2,
+                text: "two 12".to_owned()
+            })
+        );
+
+        assert_eq!(
+            Lexicon::try_from("# nothing here\n"),
+            Err(LexiconError::Empty)
+        );
+    }
+
+    #[test]
+    fn lexicon_finds_the_longest_word_at_a_position() {
+        let lexicon = Lexicon::try_from("un 1\nune 7\n").expect("lexicon should parse");
+
+        assert_eq!(lexicon.word_at("xune", 1), Some(("une", 7)));
+        assert_eq!(lexicon.word_at("xun", 1), Some(("un", 1)));
+        assert_eq!(lexicon.word_at("xun", 0), None);
+    }
+
+    #[test]
+    fn lexicon_built_ins_are_found_by_name() {
+        for name in ["english", "german", "spanish", "french"] {
+            let lexicon = name.parse::<Lexicon>().expect("built-in should exist");
+            assert!(lexicon.words().len() >= 9, "{name} is missing words");
+        }
+        assert!("klingon".parse::<Lexicon>().is_err());
+    }
+
+    #[test]
+    fn lexicon_knows_whether_it_spells_zero() {
+        assert!(!Lexicon::english().spells_zero());
+        assert!(Lexicon::german().spells_zero());
+        assert!(Lexicon::new([("nada", 0)]).spells_zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "'forty-two' spells out 42")]
+    fn lexicon_new_rejects_numbers_that_are_not_digits() {
+        Lexicon::new([("one", 1), ("forty-two", 42)]);
+    }
+}
+
+const ENGLISH: &[(&str, u32)] = &[
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+const GERMAN: &[(&str, u32)] = &[
+    ("null", 0),
+    ("eins", 1),
+    ("zwei", 2),
+    ("drei", 3),
+    ("vier", 4),
+    ("fünf", 5),
+    ("sechs", 6),
+    ("sieben", 7),
+    ("acht", 8),
+    ("neun", 9),
+];
+
+const SPANISH: &[(&str, u32)] = &[
+    ("cero", 0),
+    ("uno", 1),
+    ("dos", 2),
+    ("tres", 3),
+    ("cuatro", 4),
+    ("cinco", 5),
+    ("seis", 6),
+    ("siete", 7),
+    ("ocho", 8),
+    ("nueve", 9),
+];
+
+const FRENCH: &[(&str, u32)] = &[
+    ("zéro", 0),
+    ("un", 1),
+    ("deux", 2),
+    ("trois", 3),
+    ("quatre", 4),
+    ("cinq", 5),
+    ("six", 6),
+    ("sept", 7),
+    ("huit", 8),
+    ("neuf", 9),
+];
+
+/// The names of the built-in lexicons, for use with [`Lexicon::built_in`].
+pub const BUILT_IN_LEXICONS: &[&str] = &["english", "german", "spanish", "french"];
+
+/// A table of the words that spell out digits, such as "one" → 1 or "sieben" → 7.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexicon {
+    /// The words and their digits, longest word first so the longest match wins.
+    words: Vec<(String, u32)>,
+}
+
+/// The error when a lexicon can't be loaded.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LexiconError {
+    /// The lexicon file couldn't be read.
+    Io(String),
+    /// A line isn't a word followed by a single digit.
+    Line {
+        /// The 1-based line number.
+        line: usize,
+        /// The text of the line.
+        text: String,
+    },
+    /// The lexicon has no words in it.
+    Empty,
+    /// There is no built-in lexicon with the name.
+    UnknownBuiltIn(String),
+}
+
+impl Display for LexiconError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "unable to read lexicon: {err}"),
+            Self::Line { line, text } => write!(
+                f,
+                "line {line}: expected a word followed by a digit from 0 to 9 in '{text}'"
+            ),
+            Self::Empty => write!(f, "the lexicon has no words"),
+            Self::UnknownBuiltIn(name) => write!(
+                f,
+                "unknown lexicon '{name}', expected one of {}",
+                BUILT_IN_LEXICONS.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for LexiconError {}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Lexicon {
+    /// Creates a lexicon from words and their digits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a digit is above 9, which [`Lexicon::try_from`] reports as a [`LexiconError::Line`] instead.
+    pub fn new<S: Into<String>>(words: impl IntoIterator<Item = (S, u32)>) -> Self {
+        let mut words = words
+            .into_iter()
+            .map(|(word, digit)| (word.into(), digit))
+            .collect::<Vec<_>>();
+        if let Some((word, digit)) = words.iter().find(|(_, digit)| *digit > 9) {
+            panic!("'{word}' spells out {digit}, which isn't a digit from 0 to 9");
+        }
+        words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        Self { words }
+    }
+
+    /// The English words for one to nine, as used by the puzzle.
+    pub fn english() -> Self {
+        Self::new(ENGLISH.iter().copied())
+    }
+
+    /// The German words for zero to nine.
+    pub fn german() -> Self {
+        Self::new(GERMAN.iter().copied())
+    }
+
+    /// The Spanish words for zero to nine.
+    pub fn spanish() -> Self {
+        Self::new(SPANISH.iter().copied())
+    }
+
+    /// The French words for zero to nine.
+    pub fn french() -> Self {
+        Self::new(FRENCH.iter().copied())
+    }
+
+    /// Returns the built-in lexicon with the given name, one of [`BUILT_IN_LEXICONS`].
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "english" => Some(Self::english()),
+            "german" => Some(Self::german()),
+            "spanish" => Some(Self::spanish()),
+            "french" => Some(Self::french()),
+            _ => None,
+        }
+    }
+
+    /// Loads a lexicon file with one word and its digit per line, e.g. `sieben 7`. Blank lines and lines starting
+    /// with `#` are ignored.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LexiconError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| LexiconError::Io(err.to_string()))?;
+        Self::try_from(text.as_str())
+    }
+
+    /// The words and their digits, longest word first.
+    pub fn words(&self) -> &[(String, u32)] {
+        &self.words
+    }
+
+    /// Whether a word of the lexicon spells out zero, which decides whether written zeros count as digits too.
+    pub fn spells_zero(&self) -> bool {
+        self.words.iter().any(|(_, digit)| *digit == 0)
+    }
+
+    /// Returns the longest word that starts at byte `index` of `line`, with its digit.
+    pub fn word_at<'a>(&'a self, line: &str, index: usize) -> Option<(&'a str, u32)> {
+        let rest = line.as_bytes().get(index..)?;
+        self.words
+            .iter()
+            .find(|(word, _)| rest.starts_with(word.as_bytes()))
+            .map(|(word, digit)| (word.as_str(), *digit))
+    }
+}
+
+impl TryFrom<&str> for Lexicon {
+    type Error = LexiconError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut words = vec![];
+        for (i, line) in value.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let entry = line
+                .split_once(char::is_whitespace)
+                .and_then(|(word, digit)| Some((word, digit.trim().parse::<u32>().ok()?)))
+                .filter(|(_, digit)| *digit <= 9);
+            match entry {
+                Some(entry) => words.push(entry),
+                None => {
+                    return Err(LexiconError::Line {
+                        line: i + 1,
+                        text: line.to_owned(),
+                    })
+                }
+            }
+        }
+
+        if words.is_empty() {
+            return Err(LexiconError::Empty);
+        }
+        Ok(Self::new(words))
+    }
+}
+
+impl FromStr for Lexicon {
+    type Err = LexiconError;
+
+    /// Chooses a built-in lexicon by name, or else loads the lexicon file at the path.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::built_in(s) {
+            Some(lexicon) => Ok(lexicon),
+            None if Path::new(s).is_file() => Self::from_file(s),
+            None => Err(LexiconError::UnknownBuiltIn(s.to_owned())),
+        }
+    }
+}
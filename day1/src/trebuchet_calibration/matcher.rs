@@ -0,0 +1,337 @@
+//! A multi-pattern automaton that finds every digit and spelled-out digit in a line in one pass.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use super::{Lexicon, DECIMAL_DIGIT_ZEROS};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(matcher: &DigitMatcher, line: &str) -> Vec<(usize, usize, u32, MatchKind)> {
+        matcher
+            .find_iter(line)
+            .map(|m| (m.start, m.end, m.digit, m.kind))
+            .collect()
+    }
+
+    #[test]
+    fn digit_matcher_finds_overlapping_words() {
+        let matcher = DigitMatcher::new(&Lexicon::english());
+
+        assert_eq!(
+            matches(&matcher, "eightwo"),
+            vec![(0, 5, 8, MatchKind::Word), (4, 7, 2, MatchKind::Word)]
+        );
+        assert_eq!(
+            matches(&matcher, "twone"),
+            vec![(0, 3, 2, MatchKind::Word), (2, 5, 1, MatchKind::Word)]
+        );
+        assert_eq!(
+            matches(&matcher, "oneight7"),
+            vec![
+                (0, 3, 1, MatchKind::Word),
+                (2, 7, 8, MatchKind::Word),
+                (7, 8, 7, MatchKind::Digit)
+            ]
+        );
+    }
+
+    #[test]
+    fn digit_matcher_finds_digits_of_any_script() {
+        let matcher = DigitMatcher::new(&Lexicon::english());
+
+        assert_eq!(
+            matches(&matcher, "é\u{FF17}x\u{663}"),
+            vec![(2, 5, 7, MatchKind::Digit), (6, 8, 3, MatchKind::Digit)]
+        );
+        assert_eq!(matches(&matcher, "0\u{FF10}\u{660}"), vec![]);
+
+        let matcher = DigitMatcher::new(&Lexicon::german());
+        assert_eq!(
+            matches(&matcher, "0null\u{660}"),
+            vec![
+                (0, 1, 0, MatchKind::Digit),
+                (1, 5, 0, MatchKind::Word),
+                (5, 7, 0, MatchKind::Digit)
+            ]
+        );
+    }
+
+    #[test]
+    fn digit_matcher_reports_words_inside_longer_words() {
+        let matcher = DigitMatcher::new(&Lexicon::new([("un", 1), ("une", 7), ("neuf", 9)]));
+
+        assert_eq!(
+            matches(&matcher, "uneuf"),
+            vec![
+                (0, 2, 1, MatchKind::Word),
+                (0, 3, 7, MatchKind::Word),
+                (1, 5, 9, MatchKind::Word)
+            ]
+        );
+    }
+
+    #[test]
+    fn digit_matcher_lists_the_longest_digit_at_each_place() {
+        let matcher = DigitMatcher::new(&Lexicon::new([("un", 1), ("une", 7), ("neuf", 9)]));
+
+        let digits = matcher
+            .digits("uneuf5")
+            .iter()
+            .map(|m| m.digit)
+            .collect::<Vec<_>>();
+
+        assert_eq!(digits, vec![7, 9, 5]);
+    }
+
+    #[test]
+    fn digit_matcher_finds_the_first_and_last_digit() {
+        let matcher = DigitMatcher::new(&Lexicon::new([("un", 1), ("une", 7), ("neuf", 9)]));
+
+        let (first, last) = matcher
+            .first_and_last("xuneuf")
+            .expect("line should have digits");
+
+        assert_eq!((first.start, first.digit), (1, 7));
+        assert_eq!((last.start, last.digit), (2, 9));
+        assert_eq!(matcher.first_and_last("xyz"), None);
+        assert_eq!(matcher.first_and_last(""), None);
+    }
+}
+
+/// Whether a match was a written digit or a spelled-out word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// A decimal digit in any script, e.g. `7` or `７`.
+    Digit,
+    /// A word from the lexicon, e.g. `seven`.
+    Word,
+}
+
+/// A digit found in a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitMatch {
+    /// The byte offset where the match starts.
+    pub start: usize,
+    /// The byte offset just past the end of the match.
+    pub end: usize,
+    /// The value of the digit.
+    pub digit: u32,
+    /// Whether the digit was written or spelled out.
+    pub kind: MatchKind,
+}
+
+/// An iterator over the matches in a line, from [`DigitMatcher::find_iter`].
+#[derive(Debug, Clone)]
+pub struct Matches<'a> {
+    matcher: &'a DigitMatcher,
+    bytes: &'a [u8],
+    /// The number of bytes read so far.
+    position: usize,
+    state: usize,
+    /// The next state along the output links whose pattern hasn't been reported yet.
+    pending: Option<usize>,
+}
+
+impl Iterator for Matches<'_> {
+    type Item = DigitMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(state) = self.pending {
+                self.pending = self.matcher.output_links[state].map(|s| s as usize);
+                if let Some(pattern) = self.matcher.outputs[state] {
+                    return Some(self.matcher.to_match(pattern, self.position));
+                }
+                continue;
+            }
+
+            let byte = *self.bytes.get(self.position)?;
+            self.state = self.matcher.transitions[self.state * 256 + byte as usize] as usize;
+            self.position += 1;
+            self.pending = Some(self.state);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pattern {
+    len: usize,
+    digit: u32,
+    kind: MatchKind,
+}
+
+/// An Aho–Corasick automaton over the bytes of every decimal digit and every word of a lexicon.
+///
+/// Both digits and words are matched on their UTF-8 bytes, so matches in valid UTF-8 always start and end on character
+/// boundaries.
+#[derive(Debug, Clone)]
+pub struct DigitMatcher {
+    patterns: Vec<Pattern>,
+    /// The transition for every state and byte, `transitions[state * 256 + byte]`.
+    transitions: Vec<u32>,
+    /// The pattern that ends exactly at each state.
+    outputs: Vec<Option<usize>>,
+    /// The nearest state along the failure links that has an output.
+    output_links: Vec<Option<u32>>,
+}
+
+impl DigitMatcher {
+    /// Builds the automaton for the decimal digits of every script and the words of the lexicon.
+    ///
+    /// Written zeros are matched only if the lexicon spells out zero, so that `zero5` and `05` give the same value. The
+    /// English words of the puzzle stop at one, so its written zeros are skipped.
+    pub fn new(lexicon: &Lexicon) -> Self {
+        let digits = if lexicon.spells_zero() { 0..10 } else { 1..10 };
+        Self::build(lexicon, digits)
+    }
+
+    /// Builds the automaton for the decimal digits zero to nine of every script and no words, which are the digits
+    /// [`super::get_calibration_sum`] reads.
+    pub fn written_digits() -> Self {
+        Self::build(&Lexicon::new(std::iter::empty::<(String, u32)>()), 0..10)
+    }
+
+    fn build(lexicon: &Lexicon, digits: Range<u32>) -> Self {
+        let mut patterns = vec![];
+        let mut goto: Vec<[Option<u32>; 256]> = vec![[None; 256]];
+        let mut outputs = vec![None];
+
+        let digits = DECIMAL_DIGIT_ZEROS.iter().flat_map(|&zero| {
+            digits.clone().filter_map(move |digit| {
+                let c = char::from_u32(u32::from(zero) + digit)?;
+                Some((c.to_string(), digit, MatchKind::Digit))
+            })
+        });
+        let words = lexicon
+            .words()
+            .iter()
+            .map(|(word, digit)| (word.clone(), *digit, MatchKind::Word));
+        for (text, digit, kind) in digits.chain(words) {
+            if text.is_empty() {
+                continue;
+            }
+
+            let mut state = 0;
+            for &byte in text.as_bytes() {
+                state = match goto[state][byte as usize] {
+                    Some(next) => next as usize,
+                    None => {
+                        goto.push([None; 256]);
+                        outputs.push(None);
+                        let next = goto.len() - 1;
+                        goto[state][byte as usize] = Some(next as u32);
+                        next
+                    }
+                };
+            }
+            // The first pattern for a text wins, as with the longest-first lookup of the lexicon.
+            if outputs[state].is_none() {
+                outputs[state] = Some(patterns.len());
+                patterns.push(Pattern {
+                    len: text.len(),
+                    digit,
+                    kind,
+                });
+            }
+        }
+
+        // Fill in the failure transitions breadth first, so every state's failure state is complete before its
+        // children need it.
+        let mut transitions = vec![0_u32; goto.len() * 256];
+        let mut failures = vec![0_u32; goto.len()];
+        let mut output_links = vec![None; goto.len()];
+        let mut queue = VecDeque::new();
+        for byte in 0..256 {
+            if let Some(next) = goto[0][byte] {
+                transitions[byte] = next;
+                queue.push_back(next as usize);
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let failure = failures[state] as usize;
+            output_links[state] = match outputs[failure] {
+                Some(_) => Some(failure as u32),
+                None => output_links[failure],
+            };
+            for byte in 0..256 {
+                let index = state * 256 + byte;
+                match goto[state][byte] {
+                    Some(next) => {
+                        transitions[index] = next;
+                        failures[next as usize] = transitions[failure * 256 + byte];
+                        queue.push_back(next as usize);
+                    }
+                    None => transitions[index] = transitions[failure * 256 + byte],
+                }
+            }
+        }
+
+        Self {
+            patterns,
+            transitions,
+            outputs,
+            output_links,
+        }
+    }
+
+    /// Returns every match in the line, including overlapping ones, ordered by where they end and then longest first.
+    pub fn find_iter<'a>(&'a self, line: &'a str) -> Matches<'a> {
+        Matches {
+            matcher: self,
+            bytes: line.as_bytes(),
+            position: 0,
+            state: 0,
+            pending: None,
+        }
+    }
+
+    /// Returns the digits of the line in order, one for every place a digit starts. Where several words start at the
+    /// same place, the longest one wins.
+    pub fn digits(&self, line: &str) -> Vec<DigitMatch> {
+        let mut digits = self.find_iter(line).collect::<Vec<_>>();
+        digits.sort_by_key(|m| (m.start, std::cmp::Reverse(m.end)));
+        digits.dedup_by_key(|m| m.start);
+        digits
+    }
+
+    /// Returns the first and last digit of the line in one pass. Where several words start at the same place, the
+    /// longest one wins.
+    pub fn first_and_last(&self, line: &str) -> Option<(DigitMatch, DigitMatch)> {
+        let mut first: Option<DigitMatch> = None;
+        let mut last: Option<DigitMatch> = None;
+        let mut state = 0_usize;
+        for (i, &byte) in line.as_bytes().iter().enumerate() {
+            state = self.transitions[state * 256 + byte as usize] as usize;
+            let mut at = Some(state);
+            while let Some(s) = at {
+                if let Some(pattern) = self.outputs[s] {
+                    let found = self.to_match(pattern, i + 1);
+                    let is_earlier = |f: DigitMatch| {
+                        found.start < f.start || (found.start == f.start && found.end > f.end)
+                    };
+                    if first.is_none_or(is_earlier) {
+                        first = Some(found);
+                    }
+                    if last.is_none_or(|l| (found.start, found.end) > (l.start, l.end)) {
+                        last = Some(found);
+                    }
+                }
+                at = self.output_links[s].map(|s| s as usize);
+            }
+        }
+        Some((first?, last?))
+    }
+
+    fn to_match(&self, pattern: usize, end: usize) -> DigitMatch {
+        let Pattern { len, digit, kind } = self.patterns[pattern];
+        DigitMatch {
+            start: end - len,
+            end,
+            digit,
+            kind,
+        }
+    }
+}
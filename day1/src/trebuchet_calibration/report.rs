@@ -0,0 +1,370 @@
+//! Reports of how the calibration value of each line was derived, for auditing.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use solution::json::push_json_string;
+
+use super::rules::checked_sum;
+use super::{DigitMatch, DigitMatcher, Lexicon, MatchKind, OverflowError};
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        get_calibration_sum, get_calibration_sum_from_spelled_out_digits, DocumentGenerator,
+    };
+    use super::*;
+
+    #[test]
+    fn report_records_the_tokens_of_every_line() {
+        let report = get_calibration_report_from_spelled_out_digits(
+            "é7xeightwo\nnothing\n",
+            &Lexicon::english(),
+        );
+
+        let lines = report.lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            LineReport {
+                line: 1,
+                first: Some(ReportedToken {
+                    text: "7".to_owned(),
+                    digit: 7,
+                    kind: MatchKind::Digit,
+                    bytes: 2..3,
+                    chars: 1..2,
+                }),
+                last: Some(ReportedToken {
+                    text: "two".to_owned(),
+                    digit: 2,
+                    kind: MatchKind::Word,
+                    bytes: 8..11,
+                    chars: 7..10,
+                }),
+                value: Some(72),
+            }
+        );
+        assert_eq!(
+            lines[1],
+            LineReport {
+                line: 2,
+                first: None,
+                last: None,
+                value: None,
+            }
+        );
+        assert_eq!(report.sum(), Ok(72));
+    }
+
+    #[test]
+    fn report_of_plain_digits_ignores_words() {
+        let report = get_calibration_report("one2three4\n");
+
+        assert_eq!(report.lines()[0].value, Some(24));
+    }
+
+    #[test]
+    fn report_sums_agree_with_the_calibration_sums() {
+        let lexicon = Lexicon::english();
+        for seed in 0..20 {
+            let mut generator = DocumentGenerator::new(seed);
+            generator.lines(30).line_length(0, 40);
+            let document = generator.generate().text + "10\n105\nx\u{FF10}7\u{660}\n0two\n";
+
+            assert_eq!(
+                get_calibration_report(&document).sum(),
+                get_calibration_sum(&document),
+                "{document}"
+            );
+            assert_eq!(
+                get_calibration_report_from_spelled_out_digits(&document, &lexicon).sum(),
+                get_calibration_sum_from_spelled_out_digits(&document, &lexicon),
+                "{document}"
+            );
+        }
+
+        let report = get_calibration_report("10\n105\n");
+        assert_eq!(
+            report
+                .lines()
+                .iter()
+                .map(|line| line.value)
+                .collect::<Vec<_>>(),
+            vec![Some(10), Some(15)]
+        );
+        assert_eq!(report.sum(), Ok(25));
+    }
+
+    #[test]
+    fn report_exports_as_csv() {
+        let report =
+            get_calibration_report_from_spelled_out_digits("two1nine\n\nx", &Lexicon::english());
+
+        assert_eq!(
+            report.render(ReportFormat::Csv),
+            "line,value,\
+             first_text,first_kind,first_digit,first_byte_start,first_byte_end,first_char_start,first_char_end,\
+             last_text,last_kind,last_digit,last_byte_start,last_byte_end,last_char_start,last_char_end\n\
+             1,29,two,word,2,0,3,0,3,nine,word,9,4,8,4,8\n\
+             2,,,,,,,,,,,,,,,\n\
+             3,,,,,,,,,,,,,,,\n"
+        );
+    }
+
+    #[test]
+    fn report_exports_as_json() {
+        let report = get_calibration_report_from_spelled_out_digits("x9\n", &Lexicon::english());
+
+        assert_eq!(
+            report.render(ReportFormat::Json),
+            r#"{"lines":[{"line":1,"value":99,"first":{"text":"9","kind":"digit","digit":9,"byte_start":1,"byte_end":2,"char_start":1,"char_end":2},"last":{"text":"9","kind":"digit","digit":9,"byte_start":1,"byte_end":2,"char_start":1,"char_end":2}}]}"#
+        );
+    }
+
+    #[test]
+    fn report_quotes_csv_fields_that_need_it() {
+        let lexicon = Lexicon::new([("a,\"b\"", 1)]);
+
+        let report = get_calibration_report_from_spelled_out_digits("a,\"b\"", &lexicon);
+
+        assert!(report
+            .render(ReportFormat::Csv)
+            .contains("1,11,\"a,\"\"b\"\"\",word,1,"));
+    }
+}
+
+/// The formats a calibration report can be exported in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// One row per line, with a header row.
+    #[default]
+    Csv,
+    /// A JSON object with an array of lines.
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown report format '{other}', expected 'csv' or 'json'"
+            )),
+        }
+    }
+}
+
+/// A token that was matched as the first or last digit of a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportedToken {
+    /// The text of the token, e.g. `7` or `seven`.
+    pub text: String,
+    /// The value of the digit.
+    pub digit: u32,
+    /// Whether the token was a written digit or a spelled-out word.
+    pub kind: MatchKind,
+    /// Where the token is in the line, in bytes.
+    pub bytes: std::ops::Range<usize>,
+    /// Where the token is in the line, in characters.
+    pub chars: std::ops::Range<usize>,
+}
+
+/// How the calibration value of one line was derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineReport {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The first digit of the line, if it has any.
+    pub first: Option<ReportedToken>,
+    /// The last digit of the line, if it has any.
+    pub last: Option<ReportedToken>,
+    /// The two-digit calibration value, if the line has any digits.
+    pub value: Option<u32>,
+}
+
+/// How the calibration value of every line of a document was derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalibrationReport {
+    lines: Vec<LineReport>,
+}
+
+/// Reports how the value of each line is derived from its written digits, as in [`super::get_calibration_sum`].
+pub fn get_calibration_report(calibration_document: &str) -> CalibrationReport {
+    CalibrationReport::new(calibration_document, &DigitMatcher::written_digits())
+}
+
+/// Reports how the value of each line is derived from its written or spelled-out digits, as in
+/// [`super::get_calibration_sum_from_spelled_out_digits`].
+pub fn get_calibration_report_from_spelled_out_digits(
+    calibration_document: &str,
+    lexicon: &Lexicon,
+) -> CalibrationReport {
+    CalibrationReport::new(calibration_document, &DigitMatcher::new(lexicon))
+}
+
+impl ReportedToken {
+    fn new(line: &str, found: DigitMatch) -> Self {
+        let char_start = line[..found.start].chars().count();
+        let text = &line[found.start..found.end];
+        Self {
+            text: text.to_owned(),
+            digit: found.digit,
+            kind: found.kind,
+            bytes: found.start..found.end,
+            chars: char_start..char_start + text.chars().count(),
+        }
+    }
+}
+
+impl CalibrationReport {
+    fn new(calibration_document: &str, matcher: &DigitMatcher) -> Self {
+        let lines = calibration_document
+            .lines()
+            .enumerate()
+            .map(|(i, line)| match matcher.first_and_last(line) {
+                Some((first, last)) => LineReport {
+                    line: i + 1,
+                    value: Some(first.digit * 10 + last.digit),
+                    first: Some(ReportedToken::new(line, first)),
+                    last: Some(ReportedToken::new(line, last)),
+                },
+                None => LineReport {
+                    line: i + 1,
+                    first: None,
+                    last: None,
+                    value: None,
+                },
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// The report of every line, in document order.
+    pub fn lines(&self) -> &[LineReport] {
+        &self.lines
+    }
+
+    /// The sum of the calibration values of the lines.
+    pub fn sum(&self) -> Result<u64, OverflowError> {
+        checked_sum(
+            self.lines
+                .iter()
+                .filter_map(|line| line.value)
+                .map(u64::from),
+        )
+    }
+
+    /// Renders the report in the given format.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Json => self.to_json(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("line,value");
+        for which in ["first", "last"] {
+            for column in [
+                "text",
+                "kind",
+                "digit",
+                "byte_start",
+                "byte_end",
+                "char_start",
+                "char_end",
+            ] {
+                let _ = write!(csv, ",{which}_{column}");
+            }
+        }
+        csv.push('\n');
+
+        for line in &self.lines {
+            let _ = write!(csv, "{},", line.line);
+            if let Some(value) = line.value {
+                let _ = write!(csv, "{value}");
+            }
+            for token in [&line.first, &line.last] {
+                match token {
+                    Some(token) => {
+                        csv.push(',');
+                        push_csv_field(&mut csv, &token.text);
+                        let _ = write!(
+                            csv,
+                            ",{},{},{},{},{},{}",
+                            kind_name(token.kind),
+                            token.digit,
+                            token.bytes.start,
+                            token.bytes.end,
+                            token.chars.start,
+                            token.chars.end
+                        );
+                    }
+                    None => csv.push_str(",,,,,,,"),
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = String::from(r#"{"lines":["#);
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, r#"{{"line":{},"value":"#, line.line);
+            match line.value {
+                Some(value) => {
+                    let _ = write!(json, "{value}");
+                }
+                None => json.push_str("null"),
+            }
+            for (name, token) in [("first", &line.first), ("last", &line.last)] {
+                let _ = write!(json, r#","{name}":"#);
+                let Some(token) = token else {
+                    json.push_str("null");
+                    continue;
+                };
+                json.push_str(r#"{"text":"#);
+                push_json_string(&mut json, &token.text);
+                let _ = write!(
+                    json,
+                    r#","kind":"{}","digit":{},"byte_start":{},"byte_end":{},"char_start":{},"char_end":{}}}"#,
+                    kind_name(token.kind),
+                    token.digit,
+                    token.bytes.start,
+                    token.bytes.end,
+                    token.chars.start,
+                    token.chars.end
+                );
+            }
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+fn kind_name(kind: MatchKind) -> &'static str {
+    match kind {
+        MatchKind::Digit => "digit",
+        MatchKind::Word => "word",
+    }
+}
+
+/// Appends a CSV field, quoted if it contains a comma, quote or line break.
+fn push_csv_field(csv: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        csv.push('"');
+        csv.push_str(&value.replace('"', "\"\""));
+        csv.push('"');
+    } else {
+        csv.push_str(value);
+    }
+}
@@ -0,0 +1,289 @@
+//! Rules for turning the digits of a line into its calibration value.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io;
+use std::str::FromStr;
+
+use super::{to_decimal_digit, DigitMatcher, Lexicon};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_compute_the_value_of_a_line() {
+        let digits = [3, 1, 4, 1, 5];
+
+        assert_eq!(Rule::FirstAndLast.value(&digits), Some(35));
+        assert_eq!(Rule::FirstAndLastN(2).value(&digits), Some(3115));
+        assert_eq!(Rule::FirstAndLastN(3).value(&digits), Some(314415));
+        assert_eq!(Rule::MinAndMax.value(&digits), Some(15));
+        assert_eq!(Rule::ConcatModulo(1000).value(&digits), Some(415));
+        assert_eq!(Rule::ConcatModulo(7).value(&digits), Some(31415 % 7));
+    }
+
+    #[test]
+    fn rules_handle_short_and_empty_lines() {
+        assert_eq!(Rule::FirstAndLast.value(&[7]), Some(77));
+        assert_eq!(Rule::FirstAndLastN(2).value(&[7]), Some(77));
+        assert_eq!(Rule::FirstAndLastN(2).value(&[1, 2, 3]), Some(1223));
+        assert_eq!(Rule::FirstAndLastN(0).value(&[1, 2, 3]), None);
+        assert_eq!(Rule::ConcatModulo(0).value(&[1, 2, 3]), None);
+        for rule in [
+            Rule::FirstAndLast,
+            Rule::FirstAndLastN(2),
+            Rule::MinAndMax,
+            Rule::ConcatModulo(10),
+        ] {
+            assert_eq!(rule.value(&[]), None);
+        }
+    }
+
+    #[test]
+    fn rules_apply_to_every_line_of_a_document() {
+        let input = "two1nine\nabcone2threexyz\nnothing\n4nineeightseven2";
+        let lexicon = Lexicon::english();
+
+        assert_eq!(
+            get_calibration_sum_with_rule(input, &Rule::MinAndMax),
+            Ok(11 + 22 + 24)
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits_with_rule(
+                input,
+                &lexicon,
+                &Rule::MinAndMax
+            ),
+            Ok(19 + 13 + 29)
+        );
+    }
+
+    #[test]
+    fn rules_can_be_user_defined() {
+        let digit_sum = |digits: &[u32]| Some(digits.iter().map(|&d| u64::from(d)).sum());
+
+        let result = get_calibration_sum_from_spelled_out_digits_with_rule(
+            "two1nine\neightwo",
+            &Lexicon::english(),
+            &digit_sum,
+        );
+
+        assert_eq!(result, Ok(12 + 10));
+    }
+
+    #[test]
+    fn rules_report_totals_that_overflow() {
+        let huge = |digits: &[u32]| Some(u64::MAX / 2 + u64::from(*digits.first()?));
+
+        assert_eq!(get_calibration_sum_with_rule("0\n1", &huge), Ok(u64::MAX));
+        assert_eq!(
+            get_calibration_sum_with_rule("0\n2", &huge),
+            Err(OverflowError)
+        );
+        assert_eq!(
+            get_calibration_sum_with_rule("0\n1\nno digits", &huge),
+            Ok(u64::MAX)
+        );
+        assert_eq!(
+            get_calibration_sum_with_rule("1\n1\n1", &huge),
+            Err(OverflowError)
+        );
+    }
+
+    #[test]
+    fn rules_give_no_value_when_it_is_too_long_for_a_u64() {
+        let digits = [9; 30];
+
+        assert_eq!(
+            Rule::FirstAndLastN(9).value(&digits),
+            Some(999_999_999_999_999_999)
+        );
+        assert_eq!(Rule::FirstAndLastN(10).value(&digits), None);
+    }
+
+    #[test]
+    fn rules_concat_modulo_any_base() {
+        let digits = [9; 30];
+        let base = u64::MAX - 1;
+
+        let expected = digits.iter().fold(0_u128, |value, &digit| {
+            (value * 10 + u128::from(digit)) % u128::from(base)
+        });
+        assert_eq!(
+            Rule::ConcatModulo(base).value(&digits),
+            u64::try_from(expected).ok()
+        );
+    }
+
+    #[test]
+    fn rules_parse_from_their_names() {
+        assert_eq!("first-last".parse(), Ok(Rule::FirstAndLast));
+        assert_eq!("first-last:3".parse(), Ok(Rule::FirstAndLastN(3)));
+        assert_eq!("min-max".parse(), Ok(Rule::MinAndMax));
+        assert_eq!("concat-mod:97".parse(), Ok(Rule::ConcatModulo(97)));
+        assert!("concat-mod".parse::<Rule>().is_err());
+        assert!("first-last:x".parse::<Rule>().is_err());
+        assert!("median".parse::<Rule>().is_err());
+    }
+}
+
+/// The error when a calibration total doesn't fit in a `u64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError;
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the calibration total doesn't fit in a u64")
+    }
+}
+
+impl Error for OverflowError {}
+
+impl From<OverflowError> for io::Error {
+    fn from(err: OverflowError) -> Self {
+        io::Error::other(err)
+    }
+}
+
+/// A rule for turning the digits of a line into its calibration value.
+///
+/// Any `Fn(&[u32]) -> Option<u64>` is a rule, for rules that aren't built in.
+pub trait CalibrationRule {
+    /// Returns the calibration value of a line with the given digits, in order, or `None` if the line has no value.
+    fn value(&self, digits: &[u32]) -> Option<u64>;
+}
+
+impl<F: Fn(&[u32]) -> Option<u64>> CalibrationRule for F {
+    fn value(&self, digits: &[u32]) -> Option<u64> {
+        self(digits)
+    }
+}
+
+/// The built-in calibration rules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rule {
+    /// The first and last digit as a two-digit number, as the puzzle asks.
+    #[default]
+    FirstAndLast,
+    /// The first N digits followed by the last N digits. Lines with fewer than N digits use all of them for both, and
+    /// lines whose value is too long for a `u64` have no value.
+    FirstAndLastN(usize),
+    /// The smallest digit followed by the largest.
+    MinAndMax,
+    /// All the digits concatenated into one number, modulo the base.
+    ConcatModulo(u64),
+}
+
+impl CalibrationRule for Rule {
+    fn value(&self, digits: &[u32]) -> Option<u64> {
+        let (first, last) = (digits.first()?, digits.last()?);
+        match *self {
+            Self::FirstAndLast => concat([first, last]),
+            Self::FirstAndLastN(0) => None,
+            Self::FirstAndLastN(n) => {
+                let n = n.min(digits.len());
+                concat(digits[..n].iter().chain(&digits[digits.len() - n..]))
+            }
+            Self::MinAndMax => concat([digits.iter().min()?, digits.iter().max()?]),
+            Self::ConcatModulo(0) => None,
+            // Each step is done in a u128, as `value * 10` can overflow a u64 for bases near `u64::MAX`.
+            Self::ConcatModulo(base) => {
+                let remainder = digits.iter().fold(0_u128, |value, &digit| {
+                    (value * 10 + u128::from(digit)) % u128::from(base)
+                });
+                u64::try_from(remainder).ok()
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Parses `first-last`, `first-last:N`, `min-max` or `concat-mod:BASE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = match s.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (s, None),
+        };
+        let number = |parameter: Option<&str>| {
+            parameter
+                .ok_or_else(|| format!("The rule '{name}' needs a number, e.g. '{name}:2'"))?
+                .parse::<u64>()
+                .map_err(|err| format!("Error parsing the number of rule '{s}': {err}"))
+        };
+        match (name, parameter) {
+            ("first-last", None) => Ok(Self::FirstAndLast),
+            ("first-last", parameter) => Ok(Self::FirstAndLastN(number(parameter)? as usize)),
+            ("min-max", None) => Ok(Self::MinAndMax),
+            ("concat-mod", parameter) => Ok(Self::ConcatModulo(number(parameter)?)),
+            _ => Err(format!(
+                "Unknown rule '{s}', expected 'first-last', 'first-last:N', 'min-max' or 'concat-mod:BASE'"
+            )),
+        }
+    }
+}
+
+/// Joins digits into one number, e.g. `[4, 2]` → 42, or `None` if the number is too long for a `u64`.
+fn concat<'a>(digits: impl IntoIterator<Item = &'a u32>) -> Option<u64> {
+    digits.into_iter().try_fold(0_u64, |value, &digit| {
+        value.checked_mul(10)?.checked_add(u64::from(digit))
+    })
+}
+
+/// Sums the calibration values the rule gives the written digits of each line.
+pub fn get_calibration_sum_with_rule(
+    calibration_document: &str,
+    rule: &impl CalibrationRule,
+) -> Result<u64, OverflowError> {
+    checked_sum(
+        calibration_document
+            .lines()
+            .filter_map(|line| line_value(line, rule)),
+    )
+}
+
+/// Sums the calibration values the rule gives the written or spelled-out digits of each line.
+pub fn get_calibration_sum_from_spelled_out_digits_with_rule(
+    calibration_document: &str,
+    lexicon: &Lexicon,
+    rule: &impl CalibrationRule,
+) -> Result<u64, OverflowError> {
+    let matcher = DigitMatcher::new(lexicon);
+    checked_sum(
+        calibration_document
+            .lines()
+            .filter_map(|line| spelled_out_line_value(line, &matcher, rule)),
+    )
+}
+
+/// Adds up calibration values, failing rather than wrapping if the total doesn't fit in a `u64`.
+pub(super) fn checked_sum(values: impl IntoIterator<Item = u64>) -> Result<u64, OverflowError> {
+    values.into_iter().try_fold(0_u64, |sum, value| {
+        sum.checked_add(value).ok_or(OverflowError)
+    })
+}
+
+/// The value the rule gives the written digits of a line.
+pub(super) fn line_value(line: &str, rule: &impl CalibrationRule) -> Option<u64> {
+    let digits = line
+        .chars()
+        .filter_map(to_decimal_digit)
+        .collect::<Vec<_>>();
+    rule.value(&digits)
+}
+
+/// The value the rule gives the written or spelled-out digits of a line.
+pub(super) fn spelled_out_line_value(
+    line: &str,
+    matcher: &DigitMatcher,
+    rule: &impl CalibrationRule,
+) -> Option<u64> {
+    let digits = matcher
+        .digits(line)
+        .iter()
+        .map(|m| m.digit)
+        .collect::<Vec<_>>();
+    rule.value(&digits)
+}
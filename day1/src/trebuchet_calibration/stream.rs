@@ -0,0 +1,271 @@
+//! Calibration of documents too large to hold in memory, read line by line or in chunks across threads.
+
+use std::io::{self, BufRead, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+use super::rules::{checked_sum, line_value, spelled_out_line_value};
+use super::{CalibrationRule, DigitMatcher, Lexicon};
+
+// Only functions follow the tests, which clippy would otherwise flag.
+#[allow(clippy::items_after_test_module)]
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        get_calibration_sum_from_spelled_out_digits_with_rule, get_calibration_sum_with_rule,
+        OverflowError, Rule,
+    };
+    use super::*;
+
+    const DOCUMENT: &str = "two1nine\neightwothree\r\nabcone2threexyz\n\nxtwone3four\n\
+                            4nineeightseven2\nzoneight234\n7pqrstsixteen\nno digits";
+
+    #[test]
+    fn streaming_calibration_matches_the_in_memory_sums() {
+        let rule = Rule::default();
+        let lexicon = Lexicon::english();
+
+        assert_eq!(
+            get_calibration_sum_from_reader(DOCUMENT.as_bytes(), &rule).ok(),
+            get_calibration_sum_with_rule(DOCUMENT, &rule).ok()
+        );
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits_from_reader(
+                DOCUMENT.as_bytes(),
+                &lexicon,
+                &rule
+            )
+            .ok(),
+            Some(281)
+        );
+    }
+
+    #[test]
+    fn parallel_calibration_matches_the_in_memory_sums_for_any_chunk_size() {
+        let rule = Rule::MinAndMax;
+        let lexicon = Lexicon::english();
+        let expected =
+            get_calibration_sum_from_spelled_out_digits_with_rule(DOCUMENT, &lexicon, &rule).ok();
+        let matcher = DigitMatcher::new(&lexicon);
+
+        for chunk_size in [1, 2, 7, 64, 1 << 20] {
+            for threads in [1, 3] {
+                let result = sum_chunks_in_parallel(
+                    DOCUMENT.as_bytes(),
+                    threads,
+                    chunk_size,
+                    |line: &str| spelled_out_line_value(line, &matcher, &rule),
+                );
+                assert_eq!(result.ok(), expected, "chunks of {chunk_size}");
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_calibration_of_written_digits() {
+        let rule = Rule::default();
+
+        let result = get_calibration_sum_parallel(DOCUMENT.as_bytes(), &rule, 4);
+
+        assert_eq!(
+            result.ok(),
+            get_calibration_sum_with_rule(DOCUMENT, &rule).ok()
+        );
+    }
+
+    #[test]
+    fn streaming_calibration_reports_totals_that_overflow() {
+        let huge = |digits: &[u32]| Some(u64::MAX / 2 + u64::from(*digits.first()?));
+
+        let fits = get_calibration_sum_from_reader("0\n1\n".as_bytes(), &huge);
+        let streamed = get_calibration_sum_from_reader("1\n1\n".as_bytes(), &huge);
+        let parallel = sum_chunks_in_parallel("1\n1\n1\n".as_bytes(), 2, 2, |line: &str| {
+            line_value(line, &huge)
+        });
+
+        assert_eq!(fits.ok(), Some(u64::MAX));
+        for result in [streamed, parallel] {
+            let err = result.expect_err("the total should overflow");
+            assert_eq!(err.kind(), io::ErrorKind::Other);
+            assert_eq!(err.to_string(), OverflowError.to_string());
+        }
+    }
+
+    #[test]
+    fn parallel_calibration_stops_reading_when_every_worker_has_failed() {
+        // Far more chunks than the channel holds, so the reader would block forever if nothing received them.
+        let mut input = b"bad \xff 1\n".to_vec();
+        input.extend(b"1abc2\n".repeat(100_000));
+
+        let result = sum_chunks_in_parallel(input.as_slice(), 1, 16, |line: &str| {
+            line_value(line, &Rule::default())
+        });
+
+        assert_eq!(
+            result.map_err(|err| err.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn streaming_calibration_reports_invalid_utf8() {
+        let input: &[u8] = b"1abc2\nbad \xff 3\n";
+
+        let streamed = get_calibration_sum_from_reader(input, &Rule::default());
+        let parallel = get_calibration_sum_parallel(input, &Rule::default(), 2);
+
+        assert_eq!(
+            streamed.map_err(|err| err.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+        assert_eq!(
+            parallel.map_err(|err| err.kind()),
+            Err(io::ErrorKind::InvalidData)
+        );
+    }
+}
+
+/// The number of bytes each thread calibrates at a time, before it is extended to the end of its last line.
+const CHUNK_SIZE: usize = 4 << 20;
+
+/// Sums the calibration values the rule gives the written digits of each line, reading one line at a time.
+pub fn get_calibration_sum_from_reader(
+    reader: impl BufRead,
+    rule: &impl CalibrationRule,
+) -> io::Result<u64> {
+    sum_lines(reader, |line| line_value(line, rule))
+}
+
+/// Sums the calibration values the rule gives the written or spelled-out digits of each line, reading one line at a
+/// time.
+pub fn get_calibration_sum_from_spelled_out_digits_from_reader(
+    reader: impl BufRead,
+    lexicon: &Lexicon,
+    rule: &impl CalibrationRule,
+) -> io::Result<u64> {
+    let matcher = DigitMatcher::new(lexicon);
+    sum_lines(reader, |line| spelled_out_line_value(line, &matcher, rule))
+}
+
+/// Sums the calibration values the rule gives the written digits of each line, splitting the document into chunks of
+/// whole lines that are calibrated on up to `threads` threads.
+pub fn get_calibration_sum_parallel(
+    reader: impl BufRead,
+    rule: &(impl CalibrationRule + Sync),
+    threads: usize,
+) -> io::Result<u64> {
+    sum_chunks_in_parallel(reader, threads, CHUNK_SIZE, |line: &str| {
+        line_value(line, rule)
+    })
+}
+
+/// Sums the calibration values the rule gives the written or spelled-out digits of each line, splitting the document
+/// into chunks of whole lines that are calibrated on up to `threads` threads.
+pub fn get_calibration_sum_from_spelled_out_digits_parallel(
+    reader: impl BufRead,
+    lexicon: &Lexicon,
+    rule: &(impl CalibrationRule + Sync),
+    threads: usize,
+) -> io::Result<u64> {
+    let matcher = DigitMatcher::new(lexicon);
+    sum_chunks_in_parallel(reader, threads, CHUNK_SIZE, |line: &str| {
+        spelled_out_line_value(line, &matcher, rule)
+    })
+}
+
+fn sum_lines(mut reader: impl BufRead, value: impl Fn(&str) -> Option<u64>) -> io::Result<u64> {
+    let mut sum = 0;
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let trimmed = line.strip_suffix('\n').unwrap_or(&line);
+        let trimmed = trimmed.strip_suffix('\r').unwrap_or(trimmed);
+        sum = checked_sum([sum, value(trimmed).unwrap_or_default()])?;
+        line.clear();
+    }
+    Ok(sum)
+}
+
+/// Reads the document in chunks that end on line boundaries and sums them on worker threads. At most two chunks per
+/// thread are waiting at any time, so memory stays bounded however large the document is.
+fn sum_chunks_in_parallel(
+    mut reader: impl BufRead,
+    threads: usize,
+    chunk_size: usize,
+    value: impl Fn(&str) -> Option<u64> + Sync,
+) -> io::Result<u64> {
+    let threads = threads.max(1);
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(threads * 2);
+    // Only the workers hold the receiver, so once they have all stopped, sending fails instead of blocking forever.
+    let receiver = Arc::new(Mutex::new(receiver));
+    let failed = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                let (receiver, value, failed) = (Arc::clone(&receiver), &value, &failed);
+                scope.spawn(move || -> io::Result<u64> {
+                    let mut sum = 0;
+                    // Every worker stops as soon as one fails, since the total can't be right any more.
+                    while !failed.load(Ordering::Relaxed) {
+                        // The lock is only held while waiting for the next chunk, not while summing it.
+                        let next = receiver.lock().map(|receiver| receiver.recv());
+                        let Ok(Ok(chunk)) = next else {
+                            return Ok(sum);
+                        };
+                        let chunk_sum = std::str::from_utf8(&chunk)
+                            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+                            .and_then(|text| Ok(checked_sum(text.lines().filter_map(value))?))
+                            .and_then(|chunk_sum| Ok(checked_sum([sum, chunk_sum])?));
+                        match chunk_sum {
+                            Ok(chunk_sum) => sum = chunk_sum,
+                            Err(err) => {
+                                failed.store(true, Ordering::Relaxed);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    Ok(sum)
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(receiver);
+
+        let read = read_chunks(&mut reader, chunk_size, &sender);
+        drop(sender);
+
+        let mut sums = vec![];
+        for worker in workers {
+            sums.push(
+                worker
+                    .join()
+                    .map_err(|_| io::Error::other("a calibration thread panicked"))??,
+            );
+        }
+        read?;
+        Ok(checked_sum(sums)?)
+    })
+}
+
+fn read_chunks(
+    reader: &mut impl BufRead,
+    chunk_size: usize,
+    sender: &mpsc::SyncSender<Vec<u8>>,
+) -> io::Result<()> {
+    loop {
+        let mut chunk = Vec::with_capacity(chunk_size);
+        reader
+            .by_ref()
+            .take(chunk_size as u64)
+            .read_to_end(&mut chunk)?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        if chunk.last() != Some(&b'\n') {
+            reader.read_until(b'\n', &mut chunk)?;
+        }
+        if sender.send(chunk).is_err() {
+            // Every worker has stopped and dropped the receiver, which only happens when one of them failed.
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Calibration that rejects documents with lines it can't read, instead of skipping them.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use super::{to_decimal_digit, DigitMatcher, Lexicon};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_calibration_sums_valid_documents() {
+        let input = b"1abc2\r\npqr3stu8vwx\n\na1b2c3d4e5f\ntreb7uchet\n";
+
+        assert_eq!(get_calibration_sum_strict(input), Ok(142));
+        assert_eq!(
+            get_calibration_sum_from_spelled_out_digits_strict(
+                b"two1nine\nabcone2threexyz\n",
+                &Lexicon::english()
+            ),
+            Ok(29 + 13)
+        );
+    }
+
+    #[test]
+    fn strict_calibration_lists_every_offending_line() {
+        let input = b"1abc2\nno digits here\n7\nbad \xff byte 3\nlast one\n";
+
+        let result = get_calibration_sum_strict(input);
+
+        assert_eq!(
+            result,
+            Err(CalibrationError {
+                lines: vec![
+                    LineDiagnostic {
+                        line: 2,
+                        problem: LineProblem::NoDigits
+                    },
+                    LineDiagnostic {
+                        line: 4,
+                        problem: LineProblem::InvalidUtf8 { valid_up_to: 4 }
+                    },
+                    LineDiagnostic {
+                        line: 5,
+                        problem: LineProblem::NoDigits
+                    },
+                ]
+            })
+        );
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("3 lines of the calibration document can't be read:\n\
+                 line 2: no digits\n\
+                 line 4: invalid UTF-8 after byte 4\n\
+                 line 5: no digits"
+                .to_owned())
+        );
+    }
+
+    #[test]
+    fn strict_calibration_rejects_ambiguous_word_overlaps() {
+        let input = b"3twone\n4eightwo\n5oneight6\n";
+
+        let result = get_calibration_sum_from_spelled_out_digits_strict(input, &Lexicon::english());
+
+        assert_eq!(
+            result,
+            Err(CalibrationError {
+                lines: vec![
+                    LineDiagnostic {
+                        line: 1,
+                        problem: LineProblem::AmbiguousOverlap {
+                            chosen: "one".to_owned(),
+                            other: "two".to_owned()
+                        }
+                    },
+                    LineDiagnostic {
+                        line: 2,
+                        problem: LineProblem::AmbiguousOverlap {
+                            chosen: "two".to_owned(),
+                            other: "eight".to_owned()
+                        }
+                    },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn strict_calibration_accepts_overlaps_that_dont_change_the_value() {
+        let input = b"two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+";
+
+        let result = get_calibration_sum_from_spelled_out_digits_strict(input, &Lexicon::english());
+
+        assert_eq!(result, Ok(281));
+    }
+}
+
+/// The error when strict calibration finds lines it can't read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalibrationError {
+    /// Every offending line, in document order.
+    pub lines: Vec<LineDiagnostic>,
+}
+
+/// A line that strict calibration can't read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiagnostic {
+    /// The 1-based line number.
+    pub line: usize,
+    /// Why the line can't be read.
+    pub problem: LineProblem,
+}
+
+/// Why a line can't be read in strict mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineProblem {
+    /// The line has no digits, written or spelled out.
+    NoDigits,
+    /// The first or last digit is a word that shares letters with a word for another digit, and reading the words
+    /// without sharing letters would pick the other digit instead, e.g. the "two" at the end of "eightwo".
+    AmbiguousOverlap {
+        /// The word that was chosen as the first or last digit.
+        chosen: String,
+        /// The word that would be chosen if words couldn't share letters.
+        other: String,
+    },
+    /// The total no longer fits in a `u64` after adding this line.
+    Overflow,
+    /// The line isn't valid UTF-8.
+    InvalidUtf8 {
+        /// The number of bytes at the start of the line that are valid.
+        valid_up_to: usize,
+    },
+}
+
+impl Display for LineProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoDigits => write!(f, "no digits"),
+            Self::AmbiguousOverlap { chosen, other } => {
+                write!(f, "'{chosen}' overlaps '{other}'")
+            }
+            Self::Overflow => write!(f, "the total doesn't fit in a u64 after this line"),
+            Self::InvalidUtf8 { valid_up_to } => {
+                write!(f, "invalid UTF-8 after byte {valid_up_to}")
+            }
+        }
+    }
+}
+
+impl Display for LineDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.problem)
+    }
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.lines.len() {
+            1 => write!(f, "1 line of the calibration document can't be read:")?,
+            n => write!(f, "{n} lines of the calibration document can't be read:")?,
+        }
+        for line in &self.lines {
+            write!(f, "\n{line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CalibrationError {}
+
+/// Sums the calibration values of the document like [`super::get_calibration_sum`], but fails with every line that
+/// has no digits or isn't valid UTF-8. Blank lines are skipped.
+pub fn get_calibration_sum_strict(calibration_document: &[u8]) -> Result<u64, CalibrationError> {
+    sum_lines(calibration_document, |line| {
+        let mut digits = line.chars().filter_map(to_decimal_digit);
+        let first_digit = digits.next().ok_or(LineProblem::NoDigits)?;
+        let last_digit = digits.next_back().unwrap_or(first_digit);
+        Ok(first_digit * 10 + last_digit)
+    })
+}
+
+/// Sums the calibration values of the document like [`super::get_calibration_sum_from_spelled_out_digits`], but fails
+/// with every line that has no digits, isn't valid UTF-8, or whose first or last digit would change if words couldn't
+/// share letters. Blank lines are skipped.
+pub fn get_calibration_sum_from_spelled_out_digits_strict(
+    calibration_document: &[u8],
+    lexicon: &Lexicon,
+) -> Result<u64, CalibrationError> {
+    let matcher = DigitMatcher::new(lexicon);
+    sum_lines(calibration_document, |line| {
+        let (first, last) = matcher.first_and_last(line).ok_or(LineProblem::NoDigits)?;
+        // Read the line again from the start, skipping any word that shares letters with the one before it. Overlaps
+        // only matter where this reading picks a different digit.
+        let mut end = 0;
+        let unshared = matcher
+            .digits(line)
+            .into_iter()
+            .filter(|m| {
+                let keep = m.start >= end;
+                if keep {
+                    end = m.end;
+                }
+                keep
+            })
+            .collect::<Vec<_>>();
+        for (chosen, alternative) in [(first, unshared.first()), (last, unshared.last())] {
+            if let Some(other) = alternative.filter(|other| other.digit != chosen.digit) {
+                return Err(LineProblem::AmbiguousOverlap {
+                    chosen: line[chosen.start..chosen.end].to_owned(),
+                    other: line[other.start..other.end].to_owned(),
+                });
+            }
+        }
+        Ok(first.digit * 10 + last.digit)
+    })
+}
+
+fn sum_lines(
+    calibration_document: &[u8],
+    value: impl Fn(&str) -> Result<u32, LineProblem>,
+) -> Result<u64, CalibrationError> {
+    // The sum becomes `None` once it no longer fits in a `u64`.
+    let mut sum = Some(0_u64);
+    let mut lines = vec![];
+    for (i, line) in calibration_document.split(|&b| b == b'\n').enumerate() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let result = std::str::from_utf8(line)
+            .map_err(|err| LineProblem::InvalidUtf8 {
+                valid_up_to: err.valid_up_to(),
+            })
+            .and_then(&value);
+        match result {
+            Ok(value) => {
+                if let Some(total) = sum {
+                    sum = total.checked_add(u64::from(value));
+                    if sum.is_none() {
+                        lines.push(LineDiagnostic {
+                            line: i + 1,
+                            problem: LineProblem::Overflow,
+                        });
+                    }
+                }
+            }
+            Err(problem) => lines.push(LineDiagnostic {
+                line: i + 1,
+                problem,
+            }),
+        }
+    }
+
+    match sum {
+        Some(sum) if lines.is_empty() => Ok(sum),
+        _ => Err(CalibrationError { lines }),
+    }
+}
@@ -1,8 +1,19 @@
 //! Dice game
 #![warn(missing_docs)]
 #![warn(clippy::unwrap_used)]
-#[cfg(test)]
+mod bag;
+mod inference;
+
+pub use bag::{BagError, BagLimits, BagLimitsError, ColorBounds, UnlistedColors};
+pub use inference::{get_log_likelihood, infer_bag, BagInference, ColorPosterior, InferenceError};
 
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::num::ParseIntError;
+use std::ops::Range;
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -18,14 +29,239 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 
         let result = get_possible_game_ids_sum(
             input,
-            CubeSet {
-                red: Some(12),
-                green: Some(13),
-                blue: Some(14),
-            },
+            CubeSet::from_iter([("red", 12), ("green", 13), ("blue", 14)]),
+        );
+
+        assert_eq!(result, Ok(8))
+    }
+
+    #[test]
+    fn get_possible_game_ids_sum_works_with_any_colors() {
+        let input = r"
+Game 1: 3 yellow, 4 red; 1 black
+Game 2: 5 yellow; 2 black, 1 red
+Game 3: 1 teal
+Game 4: 2 red
+        ";
+        let bag = CubeSet::from_iter([("red", 4), ("yellow", 4), ("black", 2), ("blue", 9)]);
+
+        let result = get_possible_game_ids_sum(input, bag);
+
+        assert_eq!(result, Ok(1 + 4))
+    }
+
+    #[test]
+    fn get_sum_of_power_of_minimum_cube_sets_works_with_any_colors() {
+        let input = r"
+Game 1: 3 yellow, 4 red; 1 black, 5 yellow
+Game 2: 2 teal; 3 teal
+        ";
+
+        let result = get_sum_of_power_of_minimum_cube_sets(input);
+
+        assert_eq!(result, Ok(20 + 3))
+    }
+
+    #[test]
+    fn get_minimum_cube_set_has_only_the_revealed_colors() {
+        let game = Game::try_from("Game 7: 2 yellow, 1 red; 4 yellow; 2 red")
+            .expect("the game should parse");
+
+        let minimum = game.get_minimum_cube_set();
+
+        assert_eq!(minimum, CubeSet::from_iter([("yellow", 4), ("red", 2)]));
+        assert_eq!(minimum.get("blue"), None);
+        assert_eq!(minimum.colors().collect::<Vec<_>>(), vec!["red", "yellow"]);
+    }
+
+    #[test]
+    fn explain_impossible_games_lists_every_reveal_over_the_limit() {
+        let input = r"
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red; 1 pink
+        ";
+        let bag = CubeSet::from_iter([("red", 12), ("green", 13), ("blue", 14)]);
+
+        let result = explain_impossible_games(input, &bag).expect("the games should parse");
+
+        let violation = |reveal, color: &str, count, limit| Violation {
+            reveal,
+            color: color.to_owned(),
+            count,
+            limit,
+        };
+        assert_eq!(
+            result,
+            vec![
+                ImpossibleGame {
+                    id: 3,
+                    violations: vec![violation(1, "red", 20, 12)],
+                },
+                ImpossibleGame {
+                    id: 4,
+                    violations: vec![
+                        violation(3, "blue", 15, 14),
+                        violation(3, "red", 14, 12),
+                        violation(4, "pink", 1, 0),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            result[1].to_string(),
+            "Game 4 is impossible:\n  reveal 3: 15 blue, but the bag holds 14\n  \
+             reveal 3: 14 red, but the bag holds 12\n  reveal 4: 1 pink, but the bag holds 0"
+        );
+    }
+
+    #[test]
+    fn get_possible_game_ids_sum_handles_unlisted_colors_as_the_limits_say() {
+        let input = "Game 1: 3 red\nGame 2: 2 red, 9 pink\nGame 3: 20 red\n";
+        let limits = |unlisted| {
+            let mut limits = BagLimits::new();
+            limits
+                .bound(
+                    "red",
+                    ColorBounds {
+                        min: 1,
+                        max: Some(12),
+                    },
+                )
+                .unlisted(unlisted);
+            limits
+        };
+
+        assert_eq!(
+            get_possible_game_ids_sum(input, limits(UnlistedColors::Forbidden)),
+            Ok(1)
+        );
+        assert_eq!(
+            get_possible_game_ids_sum(input, limits(UnlistedColors::Unlimited)),
+            Ok(1 + 2)
+        );
+        assert_eq!(
+            get_possible_game_ids_sum(input, limits(UnlistedColors::Error)),
+            Err(BagError::UnlistedColor {
+                id: 2,
+                reveal: 1,
+                color: "pink".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn get_possible_game_ids_sum_adds_large_ids_without_overflowing() {
+        let input = "Game 4294967295: 1 red\nGame 1: 1 red\nGame 4294967294: 1 red\n";
+        let bag = CubeSet::from_iter([("red", 1)]);
+
+        assert_eq!(
+            get_possible_game_ids_sum(input, bag),
+            Ok(2 * u64::from(u32::MAX))
+        );
+    }
+
+    #[test]
+    fn get_possible_game_ids_sum_allows_any_number_of_colors_without_a_maximum() {
+        let input = "Game 1: 300 red\nGame 2: 2 blue\n";
+        let limits = BagLimits::try_from("red=1..").expect("the limits should parse");
+
+        assert_eq!(get_possible_game_ids_sum(input, limits), Ok(1));
+    }
+
+    #[test]
+    fn games_parse_errors_point_at_the_offending_text() {
+        let input = "Game 1: 3 blue\n\n  Game x1: 3 blue; 4 red\n";
+
+        let error = Games::try_from(input).expect_err("game 'x1' should not parse");
+
+        assert_eq!((error.line, error.columns.clone()), (3, 8..10));
+        assert_eq!(error.text, "x1");
+        assert!(matches!(error.kind, GameParseErrorKind::InvalidId(_)));
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 8: invalid game ID (invalid digit found in string) in 'x1'\n  Game x1: 3 blue; 4 red\n       ^^"
         );
+    }
+
+    #[test]
+    fn games_parse_errors_describe_each_problem() {
+        let kind = |line: &str| {
+            Game::try_from(line)
+                .map(|_| ())
+                .map_err(|e| (e.kind, e.columns))
+        };
+
+        assert_eq!(
+            kind("Game 1 3 blue"),
+            Err((GameParseErrorKind::MissingColon, 1..14))
+        );
+        assert_eq!(
+            kind("Round 1: 3 blue"),
+            Err((GameParseErrorKind::InvalidHeader, 1..8))
+        );
+        assert_eq!(
+            kind("Game 1: 3 blue;"),
+            Err((GameParseErrorKind::MissingCubes, 16..16))
+        );
+        assert_eq!(
+            kind("Game 1: 3 blue, 4"),
+            Err((GameParseErrorKind::MissingColor, 17..18))
+        );
+        assert!(matches!(
+            kind("Game 1: 3 blue, many red"),
+            Err((GameParseErrorKind::InvalidCount(_), columns)) if columns == (17..21)
+        ));
+        assert_eq!(kind("Game  12 :  3 light-blue ,4 red"), Ok(()));
+        assert_eq!(
+            kind("Game 1: 3 light blue"),
+            Err((GameParseErrorKind::UnexpectedText, 17..21))
+        );
+        assert_eq!(
+            kind("Game 1: 1 red, 2 blue; 3 red, 4  red"),
+            Err((GameParseErrorKind::DuplicateColor, 34..37))
+        );
+    }
+
+    #[test]
+    fn get_sum_of_power_of_minimum_cube_sets_returns_parse_errors() {
+        let result =
+            get_sum_of_power_of_minimum_cube_sets("Game 1: 3 blue\nGame 2: 3 blue 4 red\n");
+
+        assert!(matches!(
+            result,
+            Err(PowerError::Parse(GameParseError {
+                line: 2,
+                kind: GameParseErrorKind::UnexpectedText,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn get_sum_of_power_of_minimum_cube_sets_fails_instead_of_overflowing() {
+        let large = "Game 1: 4000000000 red, 4000000000 blue
+Game 2: 3 red
+";
+        let huge = "Game 1: 3 red
+Game 2: 4000000000 red, 4000000000 blue, 2 green
+";
+        let sum = "Game 1: 4000000000 red, 4000000000 blue
+Game 2: 4000000000 red, 4000000000 blue
+";
 
-        assert_eq!(result, 8)
+        assert_eq!(
+            get_sum_of_power_of_minimum_cube_sets(large),
+            Ok(16_000_000_000_000_000_003)
+        );
+        assert_eq!(
+            get_sum_of_power_of_minimum_cube_sets(huge),
+            Err(PowerError::Overflow { id: 2 })
+        );
+        assert_eq!(
+            get_sum_of_power_of_minimum_cube_sets(sum),
+            Err(PowerError::Overflow { id: 2 })
+        );
     }
 
     #[test]
@@ -40,206 +276,473 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 
         let result = get_sum_of_power_of_minimum_cube_sets(input);
 
-        assert_eq!(result, 2286)
+        assert_eq!(result, Ok(2286))
     }
 }
 
-/// Defines a set of cubes
-#[derive(Clone, Debug)]
+/// Defines a set of cubes, as a count for each named color.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct CubeSet {
-    /// The number of red cubes.
-    pub red: Option<u32>,
+    /// The number of cubes of each color, by color name.
+    cubes: BTreeMap<String, u32>,
+}
 
-    /// The number of green cubes.
-    pub green: Option<u32>,
+impl CubeSet {
+    /// Creates an empty set of cubes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cubes of the color, or `None` if the set doesn't list the color.
+    pub fn get(&self, color: &str) -> Option<u32> {
+        self.cubes.get(color).copied()
+    }
 
-    /// The number of blue cubes.
-    pub blue: Option<u32>,
+    /// Sets the number of cubes of the color.
+    pub fn set(&mut self, color: impl Into<String>, count: u32) -> &mut Self {
+        self.cubes.insert(color.into(), count);
+        self
+    }
+
+    /// Returns the colors in the set, in alphabetical order.
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.cubes.keys().map(String::as_str)
+    }
+
+    /// Returns each color in the set with its number of cubes, in alphabetical order of color.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.cubes
+            .iter()
+            .map(|(color, count)| (color.as_str(), *count))
+    }
+
+    /// Returns the power of the set: the numbers of cubes of each of its colors multiplied together. Returns `None` if
+    /// the power doesn't fit in a `u64`.
+    pub fn get_power(&self) -> Option<u64> {
+        self.cubes
+            .values()
+            .try_fold(1_u64, |power, &count| power.checked_mul(u64::from(count)))
+    }
 }
 
-impl CubeSet {
-    pub fn get_power(&self) -> u32 {
-        let mut power = 1;
-        if let Some(red) = self.red {
-            power *= red;
+impl<S: Into<String>> FromIterator<(S, u32)> for CubeSet {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(iter: T) -> Self {
+        let mut cube_set = CubeSet::new();
+        for (color, count) in iter {
+            cube_set.set(color, count);
         }
-        if let Some(green) = self.green {
-            power *= green;
-        }
-        if let Some(blue) = self.blue {
-            power *= blue;
+        cube_set
+    }
+}
+
+/// Gets the sum of the possible game IDs from the input if the bag were to hold cubes within the limits. A [`CubeSet`]
+/// limits the bag to at most its cubes, and forbids the colors it doesn't list.
+pub fn get_possible_game_ids_sum(
+    game_records: &str,
+    limits: impl Into<BagLimits>,
+) -> Result<u64, BagError> {
+    let games = Games::try_from(game_records)?;
+    let games = games.get_possible_in_bag(&limits.into())?;
+    games.0.iter().try_fold(0_u64, |sum, g| {
+        sum.checked_add(u64::from(g.id))
+            .ok_or(BagError::Overflow { id: g.id })
+    })
+}
+
+/// Explains why each game in the input would be impossible if the bag were to hold cubes within the limits.
+pub fn explain_impossible_games(
+    game_records: &str,
+    limits: impl Into<BagLimits>,
+) -> Result<Vec<ImpossibleGame>, BagError> {
+    let games = Games::try_from(game_records)?;
+    games.explain_impossible_in_bag(&limits.into())
+}
+
+/// A game that couldn't have been played with the cubes in the bag, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImpossibleGame {
+    /// ID of the game.
+    pub id: u32,
+
+    /// Every reveal of a color with more cubes than the bag holds.
+    pub violations: Vec<Violation>,
+}
+
+impl Display for ImpossibleGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {} is impossible:", self.id)?;
+        for violation in &self.violations {
+            write!(f, "\n  {violation}")?;
         }
-        power
+        Ok(())
     }
 }
 
-/// Gets the sum of the possible game IDs from the input if the bag were to only contain the specified cubes.
-pub fn get_possible_game_ids_sum(game_records: &str, max_cubes: CubeSet) -> u32 {
-    let games = Games::from(game_records);
-    let games = games.get_possible_with_cubes(max_cubes);
-    games.0.iter().map(|g| g.id).sum()
+/// A reveal of a color with more cubes than the bag holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The 1-based number of the reveal within its game.
+    pub reveal: usize,
+
+    /// The color that was revealed.
+    pub color: String,
+
+    /// The number of cubes of the color that were revealed.
+    pub count: u32,
+
+    /// The most cubes of the color the bag holds, which is 0 for forbidden colors.
+    pub limit: u32,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "reveal {}: {} {}, but the bag holds {}",
+            self.reveal, self.count, self.color, self.limit
+        )
+    }
 }
 
 /// Newtype pattern to "buoy" external types for the sake of defining traits.
 #[derive(Debug)]
-struct Buoy<T>(pub T);
+pub struct Buoy<T>(pub T);
 
 /// A vector of games.
-type Games = Buoy<Vec<Game>>;
+pub type Games = Buoy<Vec<Game>>;
 
 /// A game.
 #[derive(Clone, Debug)]
-struct Game {
+pub struct Game {
     /// ID of the game.
-    id: u32,
+    pub id: u32,
 
     /// Revealed cube sets.
-    cube_sets: Vec<CubeSet>,
+    pub cube_sets: Vec<CubeSet>,
 }
 
 impl Game {
+    /// Returns the fewest cubes of each color that could have been in the bag: the most of the color revealed at once.
+    /// Only colors that were revealed are in the set.
     pub fn get_minimum_cube_set(&self) -> CubeSet {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
+        let mut minimum = CubeSet::new();
         for c in &self.cube_sets {
-            if let Some(r) = c.red {
-                if r > red {
-                    red = r;
-                }
-            }
-            if let Some(g) = c.green {
-                if g > green {
-                    green = g;
-                }
-            }
-            if let Some(b) = c.blue {
-                if b > blue {
-                    blue = b;
+            for (color, count) in c.iter() {
+                if minimum.get(color).is_none_or(|most| count > most) {
+                    minimum.set(color, count);
                 }
             }
         }
+        minimum
+    }
 
-        CubeSet {
-            red: Some(red),
-            green: Some(green),
-            blue: Some(blue),
+    /// Returns every reveal of a color that has more cubes than the bag holds. Colors the limits don't list are
+    /// handled as the limits say.
+    fn get_violations(&self, limits: &BagLimits) -> Result<Vec<Violation>, BagError> {
+        let mut violations = vec![];
+        for (i, c) in self.cube_sets.iter().enumerate() {
+            for (color, count) in c.iter() {
+                let limit = match limits.get(color) {
+                    Some(bounds) => bounds.max,
+                    None => match limits.unlisted_colors() {
+                        UnlistedColors::Forbidden => Some(0),
+                        UnlistedColors::Unlimited => None,
+                        UnlistedColors::Error => {
+                            return Err(BagError::UnlistedColor {
+                                id: self.id,
+                                reveal: i + 1,
+                                color: color.to_owned(),
+                            })
+                        }
+                    },
+                };
+                if let Some(limit) = limit.filter(|&limit| count > limit) {
+                    violations.push(Violation {
+                        reveal: i + 1,
+                        color: color.to_owned(),
+                        count,
+                        limit,
+                    });
+                }
+            }
         }
+        Ok(violations)
     }
 }
 
-impl From<&str> for Games {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Games {
+    type Error = GameParseError;
+
+    /// Parses one game per line, skipping blank lines.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         let container = value
             .lines()
-            .filter_map(|line| {
-                if line.trim().is_empty() {
-                    return None;
-                }
-                Some(Game::from(line.trim()))
-            })
-            .collect::<Vec<_>>();
-        Buoy(container)
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| parse_game(i + 1, line))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Buoy(container))
     }
 }
 
 impl Games {
-    /// Returns the possible games if the bag contained only the given cubes.
-    pub fn get_possible_with_cubes(&self, max_cubes: CubeSet) -> Self {
-        let possible = self
-            .0
-            .iter()
-            .filter(|g| {
-                let red_limit = match max_cubes.red {
-                    Some(n) => n,
-                    None => 0,
-                };
-                let green_limit = match max_cubes.green {
-                    Some(n) => n,
-                    None => 0,
-                };
-                let blue_limit = match max_cubes.blue {
-                    Some(n) => n,
-                    None => 0,
-                };
-                for c in &g.cube_sets {
-                    if let Some(red) = c.red {
-                        if red > red_limit {
-                            return false;
-                        }
-                    }
-                    if let Some(green) = c.green {
-                        if green > green_limit {
-                            return false;
-                        }
-                    }
-                    if let Some(blue) = c.blue {
-                        if blue > blue_limit {
-                            return false;
-                        }
-                    }
-                }
-                true
-            })
-            .cloned()
-            .collect::<Vec<_>>();
-        Buoy(possible)
+    /// Returns the possible games if the bag held cubes within the limits. Colors the limits list that a game never
+    /// reveals don't rule the game out.
+    pub fn get_possible_in_bag(&self, limits: &BagLimits) -> Result<Self, BagError> {
+        let mut possible = vec![];
+        for g in &self.0 {
+            if g.get_violations(limits)?.is_empty() {
+                possible.push(g.clone());
+            }
+        }
+        Ok(Buoy(possible))
+    }
+
+    /// Explains why each game that isn't possible within the limits is impossible, in the order of the games.
+    pub fn explain_impossible_in_bag(
+        &self,
+        limits: &BagLimits,
+    ) -> Result<Vec<ImpossibleGame>, BagError> {
+        let mut impossible = vec![];
+        for g in &self.0 {
+            let violations = g.get_violations(limits)?;
+            if !violations.is_empty() {
+                impossible.push(ImpossibleGame {
+                    id: g.id,
+                    violations,
+                });
+            }
+        }
+        Ok(impossible)
+    }
+}
+
+impl TryFrom<&str> for Game {
+    type Error = GameParseError;
+
+    /// Parses a game record such as `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        parse_game(1, value)
+    }
+}
+
+impl TryFrom<&str> for CubeSet {
+    type Error = GameParseError;
+
+    /// Parses a reveal such as `3 blue, 4 red`.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        parse_cube_set(1, value, value)
     }
 }
 
-impl From<&str> for Game {
-    fn from(value: &str) -> Self {
-        let (id_part, record_part) = value
-            .split_once(":")
-            .expect("Game::from parse error: unable to split on :");
-        let id = id_part[5..]
+/// Parses the game record on the given line of the input.
+fn parse_game(line_number: usize, line: &str) -> Result<Game, GameParseError> {
+    let error = |part: &str, kind| GameParseError::new(line_number, line, part, kind);
+
+    let record = line.trim();
+    let (header, reveals) = record
+        .split_once(':')
+        .ok_or_else(|| error(record, GameParseErrorKind::MissingColon))?;
+    let id_part = header
+        .strip_prefix("Game")
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+        .ok_or_else(|| error(header, GameParseErrorKind::InvalidHeader))?
+        .trim();
+    let id = id_part
+        .parse::<u32>()
+        .map_err(|e| error(id_part, GameParseErrorKind::InvalidId(e)))?;
+    let cube_sets = reveals
+        .split(';')
+        .map(|reveal| parse_cube_set(line_number, line, reveal))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Game { id, cube_sets })
+}
+
+/// Parses one reveal of the game record on the given line, where `reveal` is a slice of `line`.
+fn parse_cube_set(line_number: usize, line: &str, reveal: &str) -> Result<CubeSet, GameParseError> {
+    let error = |part: &str, kind| GameParseError::new(line_number, line, part, kind);
+
+    let mut cube_set = CubeSet::new();
+    for cubes in reveal.split(',') {
+        let cubes = cubes.trim();
+        if cubes.is_empty() {
+            return Err(error(cubes, GameParseErrorKind::MissingCubes));
+        }
+        let (count_part, color) = cubes
+            .split_once(char::is_whitespace)
+            .unwrap_or((cubes, &cubes[cubes.len()..]));
+        let count = count_part
             .parse::<u32>()
-            .expect("Game::from parse error: unable to parse ID");
-        let cube_sets: Vec<CubeSet> = record_part
-            .split(';')
-            .into_iter()
-            .map(|r| CubeSet::from(r.trim()))
-            .collect();
-        Game { id, cube_sets }
-    }
-}
-
-impl From<&str> for CubeSet {
-    fn from(value: &str) -> Self {
-        value.split(", ").into_iter().fold(
-            CubeSet {
-                red: None,
-                green: None,
-                blue: None,
-            },
-            |mut cube_set, num_color_pair| {
-                let (num_part, color_part) = num_color_pair
-                    .split_once(' ')
-                    .expect("CubeSet parse error: unable to split cube set with whitespace");
-
-                let num = num_part
-                    .trim()
-                    .parse::<u32>()
-                    .expect("CubeSet parse error: unable to parse number");
-                match color_part.trim() {
-                    "red" => cube_set.red = Some(num),
-                    "green" => cube_set.green = Some(num),
-                    "blue" => cube_set.blue = Some(num),
-                    _ => panic!("Unexpected color: {color_part}"),
-                };
-                cube_set
-            },
+            .map_err(|e| error(count_part, GameParseErrorKind::InvalidCount(e)))?;
+        let (color, rest) = color
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((color.trim(), ""));
+        if color.is_empty() {
+            return Err(error(cubes, GameParseErrorKind::MissingColor));
+        }
+        let rest = rest.trim();
+        if !rest.is_empty() {
+            return Err(error(rest, GameParseErrorKind::UnexpectedText));
+        }
+        if cube_set.get(color).is_some() {
+            return Err(error(color, GameParseErrorKind::DuplicateColor));
+        }
+        cube_set.set(color, count);
+    }
+    Ok(cube_set)
+}
+
+/// A problem in the game records, along with where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameParseError {
+    /// The 1-based line number of the problem.
+    pub line: usize,
+
+    /// The 1-based columns of the offending text, end exclusive.
+    pub columns: Range<usize>,
+
+    /// The offending text.
+    pub text: String,
+
+    /// The whole line the problem is on.
+    pub line_text: String,
+
+    /// What the problem is.
+    pub kind: GameParseErrorKind,
+}
+
+impl GameParseError {
+    /// Creates an error for `part`, which is a slice of `line`.
+    fn new(line_number: usize, line: &str, part: &str, kind: GameParseErrorKind) -> Self {
+        let start = part.as_ptr() as usize - line.as_ptr() as usize;
+        let column = line[..start].chars().count() + 1;
+        Self {
+            line: line_number,
+            columns: column..column + part.chars().count(),
+            text: part.to_owned(),
+            line_text: line.to_owned(),
+            kind,
+        }
+    }
+
+    /// Returns the line with carets under the offending text, e.g.
+    ///
+    /// ```text
+    /// Game x1: 3 blue
+    ///      ^^
+    /// ```
+    pub fn snippet(&self) -> String {
+        // Tabs are kept so the carets line up however wide the terminal shows them.
+        let indent = self
+            .line_text
+            .chars()
+            .take(self.columns.start - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let carets = "^".repeat(self.columns.len().max(1));
+        format!("{}\n{indent}{carets}", self.line_text)
+    }
+}
+
+impl Display for GameParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {} in '{}'\n{}",
+            self.line,
+            self.columns.start,
+            self.kind,
+            self.text,
+            self.snippet()
         )
     }
 }
 
+impl Error for GameParseError {}
+
+/// The kinds of problems in game records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameParseErrorKind {
+    /// There is no `:` between the game ID and its reveals.
+    MissingColon,
+
+    /// The text before the `:` isn't of the form `Game <ID>`.
+    InvalidHeader,
+
+    /// The game ID is not a number.
+    InvalidId(ParseIntError),
+
+    /// A reveal, or a count and color within one, is empty.
+    MissingCubes,
+
+    /// A number of cubes is not a number.
+    InvalidCount(ParseIntError),
+
+    /// A number of cubes has no color after it.
+    MissingColor,
+
+    /// There is more text after a color, such as a missing `,` before the next count.
+    UnexpectedText,
+
+    /// A color appears more than once in the same reveal.
+    DuplicateColor,
+}
+
+impl Display for GameParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingColon => write!(f, "missing ':' after the game ID"),
+            Self::InvalidHeader => write!(f, "expected 'Game <ID>'"),
+            Self::InvalidId(e) => write!(f, "invalid game ID ({e})"),
+            Self::MissingCubes => write!(f, "missing cubes"),
+            Self::InvalidCount(e) => write!(f, "invalid number of cubes ({e})"),
+            Self::MissingColor => write!(f, "missing color"),
+            Self::UnexpectedText => write!(f, "unexpected text after the color"),
+            Self::DuplicateColor => write!(f, "color already revealed"),
+        }
+    }
+}
+
+/// Problems summing the power of the minimum cube sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerError {
+    /// The game records couldn't be parsed.
+    Parse(GameParseError),
+
+    /// The power of a game's minimum cube set, or the sum of the powers up to that game, doesn't fit in a `u64`.
+    Overflow {
+        /// ID of the game.
+        id: u32,
+    },
+}
+
+impl From<GameParseError> for PowerError {
+    fn from(err: GameParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl Display for PowerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::Overflow { id } => {
+                write!(f, "game {id}: the sum of the powers doesn't fit in a u64")
+            }
+        }
+    }
+}
+
+impl Error for PowerError {}
+
 /// Gets the sum of the power of the minimum cube sets.
-pub fn get_sum_of_power_of_minimum_cube_sets(game_records: &str) -> u32 {
-    let games = Games::from(game_records);
-    games
-        .0
-        .iter()
-        .map(|g| g.get_minimum_cube_set().get_power())
-        .sum()
+pub fn get_sum_of_power_of_minimum_cube_sets(game_records: &str) -> Result<u64, PowerError> {
+    let games = Games::try_from(game_records)?;
+    games.0.iter().try_fold(0_u64, |sum, g| {
+        g.get_minimum_cube_set()
+            .get_power()
+            .and_then(|power| sum.checked_add(power))
+            .ok_or(PowerError::Overflow { id: g.id })
+    })
 }
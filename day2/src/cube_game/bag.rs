@@ -0,0 +1,401 @@
+//! Limits on what the bag holds, given as bounds on each color and a policy for the colors they leave out.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{CubeSet, GameParseError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bag_limits_parse_bounds_and_the_unlisted_policy() {
+        let limits =
+            BagLimits::try_from("red=12, green = 2..13\n# comment\nblue=3..\nunlisted=error")
+                .expect("the limits should parse");
+
+        assert_eq!(
+            limits.get("red"),
+            Some(ColorBounds {
+                min: 0,
+                max: Some(12)
+            })
+        );
+        assert_eq!(
+            limits.get("green"),
+            Some(ColorBounds {
+                min: 2,
+                max: Some(13)
+            })
+        );
+        assert_eq!(limits.get("blue"), Some(ColorBounds { min: 3, max: None }));
+        assert_eq!(limits.get("pink"), None);
+        assert_eq!(limits.unlisted_colors(), UnlistedColors::Error);
+        assert_eq!(
+            limits.colors().collect::<Vec<_>>(),
+            vec!["blue", "green", "red"]
+        );
+    }
+
+    #[test]
+    fn bag_limits_report_invalid_entries() {
+        let error =
+            |spec: &str| BagLimits::try_from(spec).expect_err("the limits should not parse");
+
+        assert_eq!(error("red"), BagLimitsError::Entry("red".to_owned()));
+        assert_eq!(
+            error("red=x"),
+            BagLimitsError::Bounds {
+                color: "red".to_owned(),
+                text: "x".to_owned()
+            }
+        );
+        assert_eq!(
+            error("red=5..4"),
+            BagLimitsError::EmptyRange {
+                color: "red".to_owned(),
+                min: 5,
+                max: 4
+            }
+        );
+        assert_eq!(
+            error("red=1,red=2"),
+            BagLimitsError::DuplicateColor("red".to_owned())
+        );
+        assert_eq!(
+            error("unlisted=maybe"),
+            BagLimitsError::UnknownPolicy("maybe".to_owned())
+        );
+    }
+
+    #[test]
+    fn bag_limits_from_a_cube_set_forbid_unlisted_colors() {
+        let limits = BagLimits::from(CubeSet::from_iter([("red", 12)]));
+
+        assert_eq!(
+            limits.get("red"),
+            Some(ColorBounds {
+                min: 0,
+                max: Some(12)
+            })
+        );
+        assert_eq!(limits.unlisted_colors(), UnlistedColors::Forbidden);
+    }
+}
+
+/// What to do with a revealed color that the limits don't list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnlistedColors {
+    /// The bag holds none of the color, so any reveal of it makes the game impossible.
+    #[default]
+    Forbidden,
+
+    /// The bag may hold any number of the color.
+    Unlimited,
+
+    /// Revealing the color is an error, for catching typos in the limits or the game records.
+    Error,
+}
+
+impl FromStr for UnlistedColors {
+    type Err = BagLimitsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forbidden" => Ok(Self::Forbidden),
+            "unlimited" => Ok(Self::Unlimited),
+            "error" => Ok(Self::Error),
+            other => Err(BagLimitsError::UnknownPolicy(other.to_owned())),
+        }
+    }
+}
+
+/// The fewest and most cubes of one color the bag can hold, both inclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ColorBounds {
+    /// The fewest cubes of the color the bag holds.
+    pub min: u32,
+
+    /// The most cubes of the color the bag holds, or `None` if there is no limit.
+    pub max: Option<u32>,
+}
+
+/// Limits on the cubes in the bag.
+///
+/// Minimums never make a game impossible, since the bag can always hold more cubes than are revealed, but they bound
+/// the bags that are considered when inferring what the bag holds.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BagLimits {
+    /// The bounds of each listed color, by color name.
+    bounds: BTreeMap<String, ColorBounds>,
+
+    /// What to do with colors that aren't listed.
+    unlisted: UnlistedColors,
+}
+
+impl BagLimits {
+    /// Creates limits that list no colors and forbid unlisted ones.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the bounds of the color.
+    pub fn bound(&mut self, color: impl Into<String>, bounds: ColorBounds) -> &mut Self {
+        self.bounds.insert(color.into(), bounds);
+        self
+    }
+
+    /// Sets what to do with colors that aren't listed.
+    pub fn unlisted(&mut self, unlisted: UnlistedColors) -> &mut Self {
+        self.unlisted = unlisted;
+        self
+    }
+
+    /// Returns the bounds of the color, or `None` if the color isn't listed.
+    pub fn get(&self, color: &str) -> Option<ColorBounds> {
+        self.bounds.get(color).copied()
+    }
+
+    /// Returns the listed colors, in alphabetical order.
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.bounds.keys().map(String::as_str)
+    }
+
+    /// Returns what to do with colors that aren't listed.
+    pub fn unlisted_colors(&self) -> UnlistedColors {
+        self.unlisted
+    }
+
+    /// Loads limits from a file with one entry per line, in the same form as [`BagLimits::try_from`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BagLimitsError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| BagLimitsError::Io(err.to_string()))?;
+        Self::try_from(text.as_str())
+    }
+}
+
+impl From<CubeSet> for BagLimits {
+    /// Limits the bag to at most the cubes in the set, and forbids colors the set doesn't list.
+    fn from(cube_set: CubeSet) -> Self {
+        Self::from(&cube_set)
+    }
+}
+
+impl From<&CubeSet> for BagLimits {
+    /// Limits the bag to at most the cubes in the set, and forbids colors the set doesn't list.
+    fn from(cube_set: &CubeSet) -> Self {
+        let mut limits = Self::new();
+        for (color, count) in cube_set.iter() {
+            limits.bound(
+                color,
+                ColorBounds {
+                    min: 0,
+                    max: Some(count),
+                },
+            );
+        }
+        limits
+    }
+}
+
+impl TryFrom<&str> for BagLimits {
+    type Error = BagLimitsError;
+
+    /// Parses entries separated by commas or line breaks, such as `red=12, green=2..13, blue=3.., unlisted=error`.
+    ///
+    /// A color's bounds are either its maximum, or an inclusive `min..max` range where either end may be left out.
+    /// `unlisted` is `forbidden` (the default), `unlimited` or `error`. Text after a `#` is a comment.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut limits = Self::new();
+        let entries = value
+            .lines()
+            .map(|line| line.split_once('#').map_or(line, |(entry, _)| entry))
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty());
+        for entry in entries {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| BagLimitsError::Entry(entry.to_owned()))?;
+            let (key, value) = (key.trim(), value.trim());
+            if key.is_empty() {
+                return Err(BagLimitsError::Entry(entry.to_owned()));
+            }
+            if key == "unlisted" {
+                limits.unlisted(value.parse()?);
+                continue;
+            }
+            if limits.bounds.contains_key(key) {
+                return Err(BagLimitsError::DuplicateColor(key.to_owned()));
+            }
+            limits.bound(key, parse_bounds(key, value)?);
+        }
+        Ok(limits)
+    }
+}
+
+impl FromStr for BagLimits {
+    type Err = BagLimitsError;
+
+    /// Loads the limits file at the path, or else parses the limits themselves.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if Path::new(s).is_file() {
+            Self::from_file(s)
+        } else {
+            Self::try_from(s)
+        }
+    }
+}
+
+/// Parses `max`, `min..max`, `min..` or `..max`.
+fn parse_bounds(color: &str, text: &str) -> Result<ColorBounds, BagLimitsError> {
+    let number = |number: &str| {
+        number
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| BagLimitsError::Bounds {
+                color: color.to_owned(),
+                text: text.to_owned(),
+            })
+    };
+    let bounds = match text.split_once("..") {
+        None => ColorBounds {
+            min: 0,
+            max: Some(number(text)?),
+        },
+        Some((min, max)) => ColorBounds {
+            min: if min.trim().is_empty() {
+                0
+            } else {
+                number(min)?
+            },
+            max: if max.trim().is_empty() {
+                None
+            } else {
+                Some(number(max)?)
+            },
+        },
+    };
+    match bounds.max {
+        Some(max) if max < bounds.min => Err(BagLimitsError::EmptyRange {
+            color: color.to_owned(),
+            min: bounds.min,
+            max,
+        }),
+        _ => Ok(bounds),
+    }
+}
+
+/// Problems with the text of bag limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BagLimitsError {
+    /// The limits file couldn't be read.
+    Io(String),
+
+    /// An entry isn't of the form `color=bounds` or `unlisted=policy`.
+    Entry(String),
+
+    /// A color's bounds aren't a number or a range of numbers.
+    Bounds {
+        /// The color the bounds are for.
+        color: String,
+
+        /// The text of the bounds.
+        text: String,
+    },
+
+    /// A color's minimum is more than its maximum.
+    EmptyRange {
+        /// The color the bounds are for.
+        color: String,
+
+        /// The minimum.
+        min: u32,
+
+        /// The maximum.
+        max: u32,
+    },
+
+    /// A color is listed more than once.
+    DuplicateColor(String),
+
+    /// The policy for unlisted colors isn't `forbidden`, `unlimited` or `error`.
+    UnknownPolicy(String),
+}
+
+impl Display for BagLimitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "unable to read bag limits: {err}"),
+            Self::Entry(entry) => {
+                write!(f, "expected 'color=bounds' or 'unlisted=policy' but found '{entry}'")
+            }
+            Self::Bounds { color, text } => write!(
+                f,
+                "invalid bounds '{text}' for {color}, expected 'max', 'min..max', 'min..' or '..max'"
+            ),
+            Self::EmptyRange { color, min, max } => {
+                write!(f, "the minimum {min} of {color} is more than its maximum {max}")
+            }
+            Self::DuplicateColor(color) => write!(f, "{color} is listed more than once"),
+            Self::UnknownPolicy(policy) => write!(
+                f,
+                "unknown policy '{policy}' for unlisted colors, expected 'forbidden', 'unlimited' or 'error'"
+            ),
+        }
+    }
+}
+
+impl Error for BagLimitsError {}
+
+/// Problems checking games against the bag limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BagError {
+    /// The game records couldn't be parsed.
+    Parse(GameParseError),
+
+    /// A game revealed a color the limits don't list, and the limits make that an error.
+    UnlistedColor {
+        /// ID of the game.
+        id: u32,
+
+        /// The 1-based number of the reveal within its game.
+        reveal: usize,
+
+        /// The color that was revealed.
+        color: String,
+    },
+
+    /// The sum of the IDs up to a game doesn't fit in a `u64`.
+    Overflow {
+        /// ID of the game.
+        id: u32,
+    },
+}
+
+impl From<GameParseError> for BagError {
+    fn from(err: GameParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl Display for BagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "{err}"),
+            Self::UnlistedColor { id, reveal, color } => write!(
+                f,
+                "game {id}, reveal {reveal}: the bag limits don't list the color '{color}'"
+            ),
+            Self::Overflow { id } => {
+                write!(f, "game {id}: the sum of the IDs doesn't fit in a u64")
+            }
+        }
+    }
+}
+
+impl Error for BagError {}
@@ -0,0 +1,450 @@
+//! Estimates of what the bag holds, from how likely the revealed cubes are under each candidate bag.
+//!
+//! Each reveal is modelled as drawing its cubes at once, without replacement, from the full bag, so the chance of a
+//! reveal is multivariate hypergeometric. The cubes go back in the bag between reveals, so reveals are independent.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use super::{BagLimits, CubeSet, Games, UnlistedColors};
+
+/// The most candidate bags [`infer_bag`] will consider, so that loose bounds fail quickly instead of running for hours.
+const MAX_CANDIDATE_BAGS: u64 = 10_000_000;
+
+/// `ln(n!)` is looked up for `n` below this, and approximated above it.
+const LOG_FACTORIAL_TABLE_SIZE: usize = 1024;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "Game 1: 2 red; 1 red, 1 blue; 1 blue\nGame 2: 1 red, 1 green; 3 red\n";
+
+    fn games(game_records: &str) -> Games {
+        Games::try_from(game_records).expect("the games should parse")
+    }
+
+    #[test]
+    fn get_log_likelihood_is_the_hypergeometric_chance_of_every_reveal() {
+        let bag = CubeSet::from_iter([("red", 2), ("blue", 1)]);
+
+        let one_of_each = get_log_likelihood(&games("Game 1: 1 red, 1 blue"), &bag);
+        let twice = get_log_likelihood(&games("Game 1: 1 red, 1 blue; 1 blue, 1 red"), &bag);
+
+        let expected = (2.0_f64 / 3.0).ln();
+        assert!((one_of_each - expected).abs() < 1e-12);
+        assert!((twice - 2.0 * expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn get_log_likelihood_of_an_impossible_reveal_is_negative_infinity() {
+        let bag = CubeSet::from_iter([("red", 2), ("blue", 1)]);
+
+        for game in ["Game 1: 3 red", "Game 1: 2 blue", "Game 1: 1 pink"] {
+            assert_eq!(get_log_likelihood(&games(game), &bag), f64::NEG_INFINITY);
+        }
+    }
+
+    #[test]
+    fn get_log_likelihood_of_huge_bags() {
+        let bag = CubeSet::from_iter([("red", 3_000_000_000), ("blue", 3_000_000_000)]);
+
+        let log_likelihood = get_log_likelihood(&games("Game 1: 1 red, 1 blue"), &bag);
+
+        // Half of the time the two cubes differ in color.
+        assert!(
+            (log_likelihood - 0.5_f64.ln()).abs() < 1e-6,
+            "{log_likelihood}"
+        );
+    }
+
+    #[test]
+    fn log_factorials_are_exact_when_small_and_close_when_large() {
+        let model = Model::new(&games("Game 1: 1 red"), vec![]);
+        let exact = |n: u64| (1..=n).map(|i| (i as f64).ln()).sum::<f64>();
+
+        assert_eq!(model.log_factorial(0), 0.0);
+        assert!((model.log_factorial(10) - 3_628_800_f64.ln()).abs() < 1e-12);
+        for n in [1024, 1025, 5000, 100_000] {
+            let relative = (model.log_factorial(n) - exact(n)) / exact(n);
+            assert!(relative.abs() < 1e-12, "{n}: {relative}");
+        }
+    }
+
+    #[test]
+    fn infer_bag_finds_the_maximum_likelihood_bag_within_the_bounds() {
+        let limits = BagLimits::try_from("red=1..6, green=..3, blue=1..4").expect("limits");
+
+        let inference = infer_bag(&games(INPUT), &limits).expect("the bag should be inferred");
+
+        assert_eq!(
+            inference.best,
+            CubeSet::from_iter([("blue", 2), ("green", 1), ("red", 6)])
+        );
+        assert!((inference.log_likelihood - -6.705_002_417_315_66).abs() < 1e-9);
+    }
+
+    #[test]
+    fn infer_bag_reports_the_posterior_of_each_color() {
+        let limits = BagLimits::try_from("red=1..6, green=..3, blue=1..4").expect("limits");
+
+        let inference = infer_bag(&games(INPUT), &limits).expect("the bag should be inferred");
+
+        let colors = inference
+            .posteriors
+            .iter()
+            .map(|p| p.color.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(colors, vec!["blue", "green", "red"]);
+        for posterior in &inference.posteriors {
+            let total = posterior.probabilities.iter().map(|(_, p)| p).sum::<f64>();
+            assert!((total - 1.0).abs() < 1e-9, "{posterior:?}");
+        }
+        let red = &inference.posteriors[2];
+        assert_eq!(red.probabilities.len(), 6);
+        assert_eq!(red.probabilities[1], (2, 0.0));
+        assert!((red.probabilities[5].1 - 0.42).abs() < 0.001);
+        assert_eq!(inference.posteriors[1].probabilities[0], (0, 0.0));
+    }
+
+    #[test]
+    fn infer_bag_needs_bounded_colors_and_a_possible_bag() {
+        let unbounded = BagLimits::try_from("red=1.., green=3, blue=4").expect("limits");
+        let unlisted = BagLimits::try_from("red=6, blue=4, unlisted=unlimited").expect("limits");
+        let too_small = BagLimits::try_from("red=2, green=3, blue=4").expect("limits");
+        let games = games(INPUT);
+
+        assert_eq!(
+            infer_bag(&games, &unbounded),
+            Err(InferenceError::Unbounded("red".to_owned()))
+        );
+        assert_eq!(
+            infer_bag(&games, &unlisted),
+            Err(InferenceError::Unbounded("green".to_owned()))
+        );
+        assert_eq!(
+            infer_bag(&games, &too_small),
+            Err(InferenceError::NoPossibleBag)
+        );
+    }
+
+    #[test]
+    fn infer_bag_handles_bags_of_billions_of_cubes() {
+        let games = games("Game 1: 1 red, 1 blue");
+        let huge = BagLimits::try_from("red=3000000000..3000000000, blue=3000000000..3000000000")
+            .expect("limits");
+        let lopsided =
+            BagLimits::try_from("red=2000000000..2000000000, blue=1..1").expect("limits");
+
+        let huge = infer_bag(&games, &huge).expect("the bag should be inferred");
+        let lopsided = infer_bag(&games, &lopsided).expect("the bag should be inferred");
+
+        assert!((huge.log_likelihood - 0.5_f64.ln()).abs() < 1e-9);
+        assert!((lopsided.log_likelihood - (1.0_f64 / 1_000_000_000.5).ln()).abs() < 1e-9);
+    }
+}
+
+/// The estimate of what the bag holds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BagInference {
+    /// The bag within the bounds that makes the reveals most likely.
+    pub best: CubeSet,
+
+    /// The natural logarithm of the chance of every reveal, if the bag were the best bag.
+    pub log_likelihood: f64,
+
+    /// The posterior of each color, in alphabetical order of color.
+    pub posteriors: Vec<ColorPosterior>,
+}
+
+/// How likely each number of cubes of one color is, given the reveals, with every bag within the bounds equally likely
+/// beforehand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorPosterior {
+    /// The color.
+    pub color: String,
+
+    /// Each number of cubes of the color within its bounds, in order, with its probability.
+    pub probabilities: Vec<(u32, f64)>,
+}
+
+impl ColorPosterior {
+    /// Returns the expected number of cubes of the color.
+    pub fn mean(&self) -> f64 {
+        self.probabilities
+            .iter()
+            .map(|&(count, probability)| f64::from(count) * probability)
+            .sum()
+    }
+}
+
+/// Problems inferring what the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InferenceError {
+    /// A color has no maximum, so there are endless bags to consider.
+    Unbounded(String),
+
+    /// A revealed color isn't listed, and the limits make that an error.
+    UnlistedColor(String),
+
+    /// The bounds allow more bags than can be considered.
+    TooManyBags(u64),
+
+    /// No bag within the bounds could have given the reveals.
+    NoPossibleBag,
+}
+
+impl Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unbounded(color) => write!(f, "{color} needs a maximum to infer the bag"),
+            Self::UnlistedColor(color) => {
+                write!(f, "the bag limits don't list the color '{color}'")
+            }
+            Self::TooManyBags(count) => write!(
+                f,
+                "the bounds allow {count} bags, more than the {MAX_CANDIDATE_BAGS} that can be considered"
+            ),
+            Self::NoPossibleBag => write!(f, "no bag within the bounds could have given the reveals"),
+        }
+    }
+}
+
+impl Error for InferenceError {}
+
+/// Returns the natural logarithm of the chance of every reveal of the games if the bag held exactly the given cubes,
+/// which is negative infinity if any reveal is impossible.
+pub fn get_log_likelihood(games: &Games, bag: &CubeSet) -> f64 {
+    let mut colors = bag.colors().map(str::to_owned).collect::<Vec<_>>();
+    for color in revealed_colors(games) {
+        if bag.get(&color).is_none() {
+            colors.push(color);
+        }
+    }
+    let counts = colors
+        .iter()
+        .map(|color| bag.get(color).unwrap_or_default())
+        .collect::<Vec<_>>();
+    Model::new(games, colors).log_likelihood(&counts)
+}
+
+/// Finds the bag within the limits that makes the reveals of the games most likely, and the posterior of each color.
+///
+/// Every bag within the bounds is considered, so every color needs a maximum. Revealed colors that the limits don't
+/// list are held to none if they are forbidden, and need a maximum if they are unlimited.
+pub fn infer_bag(games: &Games, limits: &BagLimits) -> Result<BagInference, InferenceError> {
+    let mut bounds = BTreeMap::new();
+    for color in limits.colors() {
+        if let Some(color_bounds) = limits.get(color) {
+            bounds.insert(color.to_owned(), (color_bounds.min, color_bounds.max));
+        }
+    }
+    for color in revealed_colors(games) {
+        if bounds.contains_key(&color) {
+            continue;
+        }
+        match limits.unlisted_colors() {
+            UnlistedColors::Forbidden => bounds.insert(color, (0, Some(0))),
+            UnlistedColors::Unlimited => bounds.insert(color, (0, None)),
+            UnlistedColors::Error => return Err(InferenceError::UnlistedColor(color)),
+        };
+    }
+    let mut ranges = vec![];
+    for (color, (min, max)) in &bounds {
+        let max = max.ok_or_else(|| InferenceError::Unbounded(color.clone()))?;
+        ranges.push(*min..=max);
+    }
+    let candidates = ranges.iter().fold(1_u64, |count, range| {
+        let values = if range.is_empty() {
+            0
+        } else {
+            u64::from(range.end() - range.start()) + 1
+        };
+        count.saturating_mul(values)
+    });
+    if candidates > MAX_CANDIDATE_BAGS {
+        return Err(InferenceError::TooManyBags(candidates));
+    }
+
+    let colors = bounds.into_keys().collect::<Vec<_>>();
+    let model = Model::new(games, colors);
+
+    // The first pass finds the best bag, so the second can scale the likelihoods by it without underflowing.
+    let mut best = None;
+    for_each_bag(&ranges, |counts| {
+        let log_likelihood = model.log_likelihood(counts);
+        if log_likelihood > best.as_ref().map_or(f64::NEG_INFINITY, |(best, _)| *best) {
+            best = Some((log_likelihood, counts.to_vec()));
+        }
+    });
+    let Some((log_likelihood, best)) = best else {
+        return Err(InferenceError::NoPossibleBag);
+    };
+
+    let mut weights = ranges
+        .iter()
+        .map(|range| vec![0.0; range.clone().count()])
+        .collect::<Vec<_>>();
+    for_each_bag(&ranges, |counts| {
+        let weight = (model.log_likelihood(counts) - log_likelihood).exp();
+        for (i, count) in counts.iter().enumerate() {
+            weights[i][(count - ranges[i].start()) as usize] += weight;
+        }
+    });
+
+    let posteriors = model
+        .colors
+        .iter()
+        .zip(&ranges)
+        .zip(weights)
+        .map(|((color, range), weights)| {
+            let total = weights.iter().sum::<f64>();
+            ColorPosterior {
+                color: color.clone(),
+                probabilities: range
+                    .clone()
+                    .zip(weights)
+                    .map(|(count, weight)| (count, weight / total))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(BagInference {
+        best: model.colors.iter().cloned().zip(best).collect(),
+        log_likelihood,
+        posteriors,
+    })
+}
+
+/// The reveals of every game, as counts of a fixed list of colors.
+struct Model {
+    /// The colors, in the order of the counts.
+    colors: Vec<String>,
+
+    /// Each distinct reveal as counts of each color, with how many times it was revealed.
+    reveals: Vec<(Vec<u32>, u32)>,
+
+    /// `ln(n!)` for every `n` below [`LOG_FACTORIAL_TABLE_SIZE`].
+    log_factorials: Vec<f64>,
+}
+
+impl Model {
+    /// Creates the model of the games' reveals, for bags of the colors.
+    fn new(games: &Games, colors: Vec<String>) -> Self {
+        let mut reveals = BTreeMap::<Vec<u32>, u32>::new();
+        for c in games.0.iter().flat_map(|g| &g.cube_sets) {
+            let counts = colors
+                .iter()
+                .map(|color| c.get(color).unwrap_or_default())
+                .collect();
+            *reveals.entry(counts).or_default() += 1;
+        }
+
+        let mut log_factorials = vec![0.0];
+        for n in 1..LOG_FACTORIAL_TABLE_SIZE {
+            log_factorials.push(log_factorials[n - 1] + (n as f64).ln());
+        }
+
+        Self {
+            colors,
+            reveals: reveals.into_iter().collect(),
+            log_factorials,
+        }
+    }
+
+    /// The natural logarithm of the chance of every reveal, if the bag held the counts of each color.
+    fn log_likelihood(&self, counts: &[u32]) -> f64 {
+        let total = counts.iter().map(|&count| u64::from(count)).sum::<u64>();
+        let mut log_likelihood = 0.0;
+        for (reveal, times) in &self.reveals {
+            let drawn = reveal.iter().map(|&count| u64::from(count)).sum::<u64>();
+            if drawn > total {
+                return f64::NEG_INFINITY;
+            }
+            let mut log_chance = -self.log_choose(total, drawn);
+            for (&count, &revealed) in counts.iter().zip(reveal) {
+                log_chance += self.log_choose(u64::from(count), u64::from(revealed));
+            }
+            log_likelihood += f64::from(*times) * log_chance;
+        }
+        log_likelihood
+    }
+
+    /// `ln(n choose k)`, which is negative infinity when `k > n`.
+    fn log_choose(&self, n: u64, k: u64) -> f64 {
+        if k > n {
+            return f64::NEG_INFINITY;
+        }
+        let k = k.min(n - k);
+        self.log_falling_factorial(n, k) - self.log_factorial(k)
+    }
+
+    /// `ln(n!)`, from the table when `n` is small and from Stirling's series otherwise.
+    fn log_factorial(&self, n: u64) -> f64 {
+        if let Some(&log_factorial) = self.log_factorials.get(n as usize) {
+            return log_factorial;
+        }
+        let n = n as f64;
+        n * n.ln() - n + 0.5 * (2.0 * std::f64::consts::PI * n).ln() + stirling_correction(n)
+    }
+
+    /// `ln(n! / (n - k)!)`. Once both factorials are too large for the table, it is Stirling's series for each, with
+    /// the large terms cancelled out by hand so that huge bags don't lose all precision.
+    fn log_falling_factorial(&self, n: u64, k: u64) -> f64 {
+        let m = n - k;
+        if m < LOG_FACTORIAL_TABLE_SIZE as u64 {
+            return self.log_factorial(n) - self.log_factorial(m);
+        }
+        let (n, k, m) = (n as f64, k as f64, m as f64);
+        k * n.ln() - (m + 0.5) * (-k / n).ln_1p() - k + stirling_correction(n)
+            - stirling_correction(m)
+    }
+}
+
+/// The terms of Stirling's series for `ln(n!)` after `n ln n - n + ln(2πn) / 2`, which are accurate to about one part
+/// in 10^15 for `n` of at least [`LOG_FACTORIAL_TABLE_SIZE`].
+fn stirling_correction(n: f64) -> f64 {
+    1.0 / (12.0 * n) - 1.0 / (360.0 * n.powi(3)) + 1.0 / (1260.0 * n.powi(5))
+}
+
+/// The colors revealed in any game, in alphabetical order.
+fn revealed_colors(games: &Games) -> Vec<String> {
+    let mut colors = games
+        .0
+        .iter()
+        .flat_map(|g| &g.cube_sets)
+        .flat_map(|c| c.colors())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    colors.sort();
+    colors.dedup();
+    colors
+}
+
+/// Calls `f` with every combination of counts within the ranges, varying the last count fastest.
+fn for_each_bag(ranges: &[std::ops::RangeInclusive<u32>], mut f: impl FnMut(&[u32])) {
+    if ranges.iter().any(|range| range.is_empty()) {
+        return;
+    }
+    let mut counts = ranges
+        .iter()
+        .map(|range| *range.start())
+        .collect::<Vec<_>>();
+    loop {
+        f(&counts);
+        let mut i = counts.len();
+        loop {
+            if i == 0 {
+                return;
+            }
+            i -= 1;
+            if counts[i] < *ranges[i].end() {
+                counts[i] += 1;
+                break;
+            }
+            counts[i] = *ranges[i].start();
+        }
+    }
+}
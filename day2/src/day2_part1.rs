@@ -1,4 +1,4 @@
-mod cube_game;
+use day2::cube_game;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -7,13 +7,36 @@ fn main() {
         .expect("Please supply an input file as the first argument");
     let input = std::fs::read_to_string(&args[1])
         .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"));
-    let value = cube_game::get_possible_game_ids_sum(
-        &input,
-        cube_game::CubeSet {
-            red: Some(12),
-            green: Some(13),
-            blue: Some(14),
-        },
-    );
+
+    // `--bag` takes the bag limits or a file of them, e.g. `--bag red=12,green=2..13,blue=..14,unlisted=error`.
+    let bag = match args.iter().position(|arg| arg == "--bag") {
+        Some(i) => args
+            .get(i + 1)
+            .expect("Please supply bag limits or a file of them after --bag")
+            .parse::<cube_game::BagLimits>()
+            .unwrap_or_else(|err| panic!("{err}")),
+        None => cube_game::BagLimits::from(cube_game::CubeSet::from_iter([
+            ("red", 12),
+            ("green", 13),
+            ("blue", 14),
+        ])),
+    };
+
+    // `--explain` prints why each impossible game is impossible before the sum.
+    if args.iter().any(|arg| arg == "--explain") {
+        let impossible =
+            cube_game::explain_impossible_games(&input, bag.clone()).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                std::process::exit(1)
+            });
+        for game in impossible {
+            println!("{game}");
+        }
+    }
+
+    let value = cube_game::get_possible_game_ids_sum(&input, bag).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1)
+    });
     println!("Sum of game IDs = {value}");
 }
@@ -1,4 +1,4 @@
-mod cube_game;
+use day2::cube_game;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -7,6 +7,9 @@ fn main() {
         .expect("Please supply an input file as the first argument");
     let input = std::fs::read_to_string(&args[1])
         .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"));
-    let value = cube_game::get_sum_of_power_of_minimum_cube_sets(&input);
+    let value = cube_game::get_sum_of_power_of_minimum_cube_sets(&input).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1)
+    });
     println!("Sum of game IDs = {value}");
 }
@@ -0,0 +1,26 @@
+//! Day 2: Cube Conundrum
+
+use solution::{Answer, Solution};
+use std::error::Error;
+
+pub mod cube_game;
+
+/// Solution for day 2.
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn day(&self) -> u32 {
+        2
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        let bag = cube_game::CubeSet::from_iter([("red", 12), ("green", 13), ("blue", 14)]);
+        Ok(Box::new(cube_game::get_possible_game_ids_sum(input, bag)?))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(cube_game::get_sum_of_power_of_minimum_cube_sets(
+            input,
+        )?))
+    }
+}
@@ -1,4 +1,4 @@
-mod gondola_lift_engine;
+use day3::gondola_lift_engine;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
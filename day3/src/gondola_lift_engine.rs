@@ -81,6 +81,8 @@ impl EngineSchematics {
         Some(offset as usize)
     }
 
+    // Clippy would rather the match at the end were written with `Option::map`.
+    #[allow(clippy::manual_map)]
     fn get_numbers_adjacent_to(&self, x: usize, y: usize) -> Vec<u64> {
         // Left and right are the simplest cases since there is no potential for multiple numbers
         // on either side.
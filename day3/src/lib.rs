@@ -0,0 +1,27 @@
+//! Day 3: Gear Ratios
+
+use solution::{Answer, Solution};
+use std::error::Error;
+
+pub mod gondola_lift_engine;
+
+/// Solution for day 3.
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn day(&self) -> u32 {
+        3
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(
+            gondola_lift_engine::get_sum_of_all_engine_part_numbers(input),
+        ))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(
+            gondola_lift_engine::get_sum_of_all_engine_gear_ratios(input),
+        ))
+    }
+}
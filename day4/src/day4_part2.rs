@@ -1,4 +1,4 @@
-mod scratch_cards;
+use day4::scratch_cards;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -0,0 +1,27 @@
+//! Day 4: Scratchcards
+
+use solution::{Answer, Solution};
+use std::error::Error;
+
+pub mod scratch_cards;
+
+/// Solution for day 4.
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn day(&self) -> u32 {
+        4
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(scratch_cards::get_total_scratch_card_points(
+            input,
+        )))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        Ok(Box::new(scratch_cards::get_total_number_of_scratch_cards(
+            input,
+        )))
+    }
+}
@@ -3,6 +3,7 @@
 #![warn(clippy::unwrap_used)]
 
 use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
 
 #[cfg(test)]
 mod test {
@@ -94,7 +95,7 @@ impl From<&str> for ScratchCardData {
             .collect();
         match card_data {
             Ok(card_data) => Self { card_data },
-            Err(e) => panic!("{e:?}"),
+            Err(e) => panic!("{e}"),
         }
     }
 }
@@ -129,6 +130,16 @@ enum ParseError {
     Contents(String),
 }
 
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyLine => write!(f, "empty line"),
+            Self::Header(text) => write!(f, "invalid card header: {text}"),
+            Self::Contents(text) => write!(f, "invalid card numbers: {text}"),
+        }
+    }
+}
+
 impl TryFrom<&str> for CardData {
     type Error = ParseError;
 
@@ -2,16 +2,26 @@
 #![warn(missing_docs)]
 #![warn(clippy::unwrap_used)]
 
-use std::collections::HashSet;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::num::ParseIntError;
+use std::ops::Range;
+
+mod json;
+mod lint;
+mod serialize;
+mod trace;
+
+pub use lint::{LintReport, LintedMapping, MapLint};
+pub use trace::{Trace, TraceFormat, TraceStep};
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use solution::rng::XorShift;
 
-    #[test]
-    fn day5_get_lowest_location_number_that_corresponds_to_any_initial_seed_number_returns_the_correct_answer(
-    ) {
-        let almanac_text = r"
+    pub(super) const ALMANAC_TEXT: &str = r"
 seeds: 79 14 55 13
 
 seed-to-soil map:
@@ -47,144 +57,1241 @@ humidity-to-location map:
 56 93 4
         ";
 
+    #[test]
+    fn day5_get_lowest_location_number_that_corresponds_to_any_initial_seed_number_returns_the_correct_answer(
+    ) {
         let result =
-            get_lowest_location_number_that_corresponds_to_any_initial_seed_number(almanac_text);
+            get_lowest_location_number_that_corresponds_to_any_initial_seed_number(ALMANAC_TEXT);
 
         assert_eq!(result, 35);
     }
+
+    #[test]
+    fn day5_get_lowest_location_number_that_corresponds_to_any_initial_seed_range_returns_the_correct_answer(
+    ) {
+        let result =
+            get_lowest_location_number_that_corresponds_to_any_initial_seed_range(ALMANAC_TEXT);
+
+        assert_eq!(result, 46);
+    }
+
+    #[test]
+    fn day5_get_ranges_splits_ranges_at_mapping_boundaries() {
+        let map = MappingTable::try_from(vec![Mapping::new(100, 10, 5)])
+            .expect("mapping table should be valid");
+
+        let mut result = map.get_ranges(&[0..3, 5..20]);
+        result.sort_unstable_by_key(|r| r.start);
+
+        assert_eq!(result, vec![0..3, 5..10, 15..20, 100..105]);
+    }
+
+    #[test]
+    fn day5_mapping_table_get_treats_source_ranges_as_half_open() {
+        let map = MappingTable::try_from(vec![Mapping::new(100, 10, 5)])
+            .expect("mapping table should be valid");
+
+        assert_eq!(map.get(9), 9);
+        assert_eq!(map.get(10), 100);
+        assert_eq!(map.get(14), 104);
+        assert_eq!(map.get(15), 15);
+    }
+
+    #[test]
+    fn day5_mapping_table_rejects_overlapping_source_ranges() {
+        let result = MappingTable::try_from(vec![Mapping::new(100, 10, 5), Mapping::new(0, 14, 2)]);
+
+        assert_eq!(
+            result,
+            Err(MappingTableError::Overlap {
+                existing: Mapping::new(100, 10, 5),
+                incoming: Mapping::new(0, 14, 2),
+            })
+        );
+    }
+
+    #[test]
+    fn day5_mapping_table_rejects_overlaps_in_any_order() {
+        let result = MappingTable::try_from(vec![
+            Mapping::new(0, 0, 100),
+            Mapping::new(0, 200, 10),
+            Mapping::new(0, 150, 0),
+            Mapping::new(0, 50, 10),
+        ]);
+
+        assert_eq!(
+            result,
+            Err(MappingTableError::Overlap {
+                existing: Mapping::new(0, 0, 100),
+                incoming: Mapping::new(0, 50, 10),
+            })
+        );
+    }
+
+    #[test]
+    fn day5_mapping_table_rejects_ranges_that_overflow() {
+        let result = MappingTable::try_from(vec![Mapping::new(0, u64::MAX - 1, 2)]);
+
+        assert_eq!(
+            result,
+            Err(MappingTableError::Overflow(Mapping::new(
+                0,
+                u64::MAX - 1,
+                2
+            )))
+        );
+    }
+
+    #[test]
+    fn day5_build_fails_when_a_map_has_overlapping_mappings() {
+        let result = AlmanacBuilder::new()
+            .text("seeds: 1\nseed-to-soil map:\n50 98 2\n52 99 48\n")
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                line: 4,
+                column: 1,
+                kind: AlmanacParseErrorKind::Mapping(MappingTableError::Overlap { .. }),
+                ..
+            }))
+        ));
+    }
+
+    fn build_error(almanac_text: &str) -> AlmanacParseError {
+        match AlmanacBuilder::new().text(almanac_text).build() {
+            Err(AlmanacBuilderError::Parse(e)) => e,
+            Err(e) => panic!("expected a parse error but got {e:?}"),
+            Ok(_) => panic!("expected a parse error but the almanac was built"),
+        }
+    }
+
+    #[test]
+    fn day5_build_reports_where_a_number_is_invalid() {
+        let error = build_error("seeds: 79 14\n\nseed-to-soil map:\n50 98x 2\n");
+
+        assert_eq!((error.line, error.column), (4, 4));
+        assert_eq!(error.text, "98x");
+        assert!(matches!(
+            error.kind,
+            AlmanacParseErrorKind::InvalidNumber(_)
+        ));
+    }
+
+    #[test]
+    fn day5_build_reports_data_before_any_header() {
+        let error = build_error("  79 14\nseeds: 1\n");
+
+        assert_eq!(
+            error,
+            AlmanacParseError {
+                line: 1,
+                column: 3,
+                text: "79 14".to_owned(),
+                kind: AlmanacParseErrorKind::DataBeforeHeader,
+            }
+        );
+    }
+
+    #[test]
+    fn day5_build_reports_map_rows_without_exactly_three_values() {
+        let short = build_error("seeds: 1\nseed-to-soil map:\n50 98\n");
+        let long = build_error("seeds: 1\nseed-to-soil map:\n50 98 2 7\n");
+
+        assert_eq!(
+            short.kind,
+            AlmanacParseErrorKind::WrongNumberOfValues {
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!((short.line, short.column), (3, 1));
+        assert_eq!(
+            long.kind,
+            AlmanacParseErrorKind::WrongNumberOfValues {
+                expected: 3,
+                found: 4
+            }
+        );
+    }
+
+    #[test]
+    fn day5_build_reports_unknown_headers() {
+        let error = build_error("seeds: 1\nseed to soil map:\n50 98 2\n");
+
+        assert_eq!(
+            error,
+            AlmanacParseError {
+                line: 2,
+                column: 1,
+                text: "seed to soil map:".to_owned(),
+                kind: AlmanacParseErrorKind::UnknownHeader,
+            }
+        );
+    }
+
+    #[test]
+    fn day5_build_reports_duplicate_sections() {
+        let error =
+            build_error("seeds: 1\nseed-to-soil map:\n50 98 2\n\nseed-to-soil map:\n0 0 1\n");
+
+        assert_eq!((error.line, error.column), (5, 1));
+        assert_eq!(error.kind, AlmanacParseErrorKind::DuplicateSection);
+        assert_eq!(
+            error.to_string(),
+            "line 5, column 1: duplicate section in 'seed-to-soil map:'"
+        );
+    }
+
+    #[test]
+    fn day5_build_reports_an_unpaired_seed_when_reading_seed_ranges() {
+        let text = "seeds: 79 14\n  55\nseed-to-location map:\n50 98 2\n";
+        let result = AlmanacBuilder::new().text(text).seed_ranges(true).build();
+
+        assert_eq!(
+            result,
+            Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                line: 2,
+                column: 3,
+                text: "55".to_owned(),
+                kind: AlmanacParseErrorKind::UnpairedSeed,
+            }))
+        );
+
+        let almanac = AlmanacBuilder::new()
+            .text(text)
+            .build()
+            .expect("almanac should build");
+        assert_eq!(
+            almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_number(),
+            Ok(14)
+        );
+        assert_eq!(
+            almanac.get_initial_seed_ranges(),
+            Err(AlmanacError::UnpairedSeed)
+        );
+        assert_eq!(
+            almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_range(),
+            Err(AlmanacError::UnpairedSeed)
+        );
+    }
+
+    #[test]
+    fn day5_seed_ranges_running_past_the_largest_u64_are_rejected() {
+        let text = "seeds: 1 2\n  18446744073709551600 100\nseed-to-location map:\n50 98 2\n";
+        let expected = AlmanacParseError {
+            line: 2,
+            column: 3,
+            text: "18446744073709551600 100".to_owned(),
+            kind: AlmanacParseErrorKind::SeedRangeOverflow,
+        };
+
+        assert_eq!(
+            AlmanacBuilder::new().text(text).seed_ranges(true).build(),
+            Err(AlmanacBuilderError::Parse(expected.clone()))
+        );
+        assert_eq!(
+            AlmanacBuilder::new().text(text).lint(),
+            Err(AlmanacBuilderError::Parse(expected))
+        );
+
+        let almanac = AlmanacBuilder::new()
+            .text(text)
+            .build()
+            .expect("almanac should build");
+        assert_eq!(
+            almanac.get_initial_seed_ranges(),
+            Err(AlmanacError::SeedRangeOverflow {
+                start: 18_446_744_073_709_551_600,
+                length: 100,
+            })
+        );
+
+        let almanac = AlmanacBuilder::new()
+            .json(almanac.to_json())
+            .build()
+            .expect("almanac should build from JSON");
+        let error = AlmanacBuilder::new()
+            .json(almanac.to_json())
+            .seed_ranges(true)
+            .build()
+            .expect_err("the JSON seed range should be rejected");
+        assert!(matches!(
+            error,
+            AlmanacBuilderError::Parse(AlmanacParseError {
+                kind: AlmanacParseErrorKind::SeedRangeOverflow,
+                ..
+            })
+        ));
+
+        // A range may end exactly at the largest u64.
+        let almanac = AlmanacBuilder::new()
+            .text("seeds: 18446744073709551515 100\nseed-to-location map:\n50 98 2\n")
+            .seed_ranges(true)
+            .build()
+            .expect("almanac should build");
+        let ranges = almanac
+            .get_initial_seed_ranges()
+            .expect("the seed range should fit");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 18_446_744_073_709_551_515..u64::MAX);
+    }
+
+    #[test]
+    fn day5_the_largest_location_is_an_explicit_error() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        assert_eq!(
+            almanac.get_seed_ranges_for_location(u64::MAX),
+            Err(AlmanacError::UnrepresentableValue(u64::MAX))
+        );
+        assert_eq!(
+            almanac.map_value(u64::MAX, SEED_CATEGORY, LOCATION_CATEGORY),
+            Ok(u64::MAX)
+        );
+        let seeds = almanac
+            .get_seed_ranges_for_location(u64::MAX - 1)
+            .expect("the location should lead back to seeds");
+        assert_eq!(seeds.len(), 1);
+        assert_eq!(seeds[0], u64::MAX - 1..u64::MAX);
+    }
+
+    #[test]
+    fn day5_lowest_location_is_an_error_without_any_seeds() {
+        for text in [
+            "seeds:\nseed-to-location map:\n0 1 2\n",
+            "seeds: 79 0\nseed-to-location map:\n0 1 2\n",
+        ] {
+            let almanac = AlmanacBuilder::new()
+                .text(text)
+                .seed_ranges(true)
+                .build()
+                .expect("almanac should build");
+
+            assert_eq!(
+                almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_range(),
+                Err(AlmanacError::NoSeeds)
+            );
+            assert_eq!(
+                almanac
+                    .get_lowest_location_number_that_corresponds_to_any_initial_seed_range_in_reverse(),
+                Err(AlmanacError::NoSeeds)
+            );
+        }
+
+        let almanac = AlmanacBuilder::new()
+            .text("seeds:\nseed-to-location map:\n0 1 2\n")
+            .build()
+            .expect("almanac should build");
+        assert_eq!(
+            almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_number(),
+            Err(AlmanacError::NoSeeds)
+        );
+    }
+
+    #[test]
+    fn day5_map_value_follows_the_chain_of_maps_between_any_two_categories() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        // Seed 79 corresponds to soil 81, fertilizer 81, water 81, light 74, temperature 78, humidity 78, location 82.
+        assert_eq!(almanac.map_value(79, "seed", "location"), Ok(82));
+        assert_eq!(almanac.map_value(81, "soil", "light"), Ok(74));
+        assert_eq!(almanac.map_value(74, "light", "light"), Ok(74));
+    }
+
+    #[test]
+    fn day5_map_value_returns_an_error_when_no_chain_of_maps_exists() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        assert_eq!(
+            almanac.map_value(82, "location", "seed"),
+            Err(AlmanacError::NoPath {
+                from: "location".to_owned(),
+                to: "seed".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn day5_build_accepts_any_category_names() {
+        let almanac = AlmanacBuilder::new()
+            .text(
+                r"
+seeds: 1 2
+
+seed-to-sprout map:
+10 0 5
+
+sprout-to-tree map:
+100 10 5
+        ",
+            )
+            .build()
+            .expect("almanac should build");
+
+        assert_eq!(almanac.map_value(3, "seed", "tree"), Ok(103));
+        assert_eq!(almanac.maps().len(), 2);
+    }
+
+    #[test]
+    fn day5_compose_combines_the_chain_of_maps_into_one_table() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        let seed_to_location = almanac
+            .compose(SEED_CATEGORY, LOCATION_CATEGORY)
+            .expect("seeds should lead to locations");
+
+        assert_eq!(seed_to_location.get(79), 82);
+        assert_eq!(seed_to_location.get(14), 43);
+        assert_eq!(seed_to_location.get(55), 86);
+        assert_eq!(seed_to_location.get(13), 35);
+    }
+
+    #[test]
+    fn day5_compose_normalizes_the_combined_table() {
+        let first = MappingTable::try_from(vec![Mapping::new(10, 0, 5), Mapping::new(15, 5, 5)])
+            .expect("mapping table should be valid");
+        let second = MappingTable::try_from(vec![Mapping::new(0, 10, 10)])
+            .expect("mapping table should be valid");
+
+        // The first table moves 0..10 up by 10 and the second moves it straight back down, so only 10..20, which the
+        // first table leaves alone, is moved.
+        assert_eq!(
+            first.compose(&second).mappings(),
+            &[Mapping::new(0, 10, 10)]
+        );
+        assert_eq!(
+            first.compose(&MappingTable::new()).mappings(),
+            &[Mapping::new(10, 0, 10)]
+        );
+    }
+
+    #[test]
+    fn day5_compose_agrees_with_looking_up_each_map_in_turn() {
+        let mut rng = XorShift::new(0x2023_1205);
+
+        for _ in 0..50 {
+            let almanac_text = generate_almanac_text(&mut rng);
+            let almanac = AlmanacBuilder::new()
+                .text(almanac_text.as_str())
+                .build()
+                .expect("generated almanac should build");
+            let seed_to_location = almanac
+                .compose(SEED_CATEGORY, LOCATION_CATEGORY)
+                .expect("seeds should lead to locations");
+
+            for _ in 0..200 {
+                let seed = rng.next_u64() % 1_200;
+                let expected = almanac
+                    .maps()
+                    .iter()
+                    .fold(seed, |value, map| map.table().get(value));
+                assert_eq!(seed_to_location.get(seed), expected, "{almanac_text}");
+            }
+
+            let mappings = seed_to_location.mappings();
+            assert!(mappings.iter().all(|mapping| mapping.offset() != 0));
+            assert!(mappings
+                .windows(2)
+                .all(|pair| pair[0].source_range().end <= pair[1].source));
+        }
+    }
+
+    #[test]
+    fn day5_get_seed_ranges_for_location_finds_every_seed_that_leads_there() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        let seeds = almanac
+            .get_seed_ranges_for_location(82)
+            .expect("locations should lead back to seeds");
+
+        assert!(seeds.iter().any(|range| range.contains(&79)));
+        for seed in seeds.iter().flat_map(|range| range.clone()) {
+            assert_eq!(
+                almanac.map_value(seed, SEED_CATEGORY, LOCATION_CATEGORY),
+                Ok(82)
+            );
+        }
+    }
+
+    #[test]
+    fn day5_get_source_ranges_includes_values_that_are_not_mapped() {
+        let map = MappingTable::try_from(vec![Mapping::new(100, 10, 5)])
+            .expect("mapping table should be valid");
+
+        let mut result = map.get_source_ranges(&[0..20, 102..104]);
+        result.sort_unstable_by_key(|r| r.start);
+
+        assert_eq!(result, vec![0..10, 12..14, 15..20, 102..104]);
+    }
+
+    #[test]
+    fn day5_reverse_lookup_agrees_with_forward_lookup_for_seed_ranges() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+        assert_eq!(
+            almanac
+                .get_lowest_location_number_that_corresponds_to_any_initial_seed_range_in_reverse(),
+            Ok(46)
+        );
+
+        let mut rng = XorShift::new(0x5eed_5eed);
+        for _ in 0..50 {
+            let almanac_text = generate_almanac_text(&mut rng);
+            let almanac = AlmanacBuilder::new()
+                .text(almanac_text.as_str())
+                .build()
+                .expect("generated almanac should build");
+
+            assert_eq!(
+                almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_range_in_reverse(),
+                almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_range(),
+                "{almanac_text}"
+            );
+        }
+    }
+
+    /// Generates the text of an almanac with a few seed ranges and the usual seven maps, each with a few random,
+    /// non-overlapping mappings.
+    pub(super) fn generate_almanac_text(rng: &mut XorShift) -> String {
+        let categories = [
+            "seed",
+            "soil",
+            "fertilizer",
+            "water",
+            "light",
+            "temperature",
+            "humidity",
+            "location",
+        ];
+        let mut text = String::from("seeds:");
+        for _ in 0..1 + rng.next_u64() % 3 {
+            text.push_str(&format!(
+                " {} {}",
+                rng.next_u64() % 1_000,
+                1 + rng.next_u64() % 200
+            ));
+        }
+        text.push('\n');
+
+        for pair in categories.windows(2) {
+            text.push_str(&format!("\n{}-to-{} map:\n", pair[0], pair[1]));
+            let mut source = 0;
+            for _ in 0..rng.next_u64() % 6 {
+                source += rng.next_u64() % 100;
+                let count = 1 + rng.next_u64() % 150;
+                let destination = rng.next_u64() % 1_000;
+                text.push_str(&format!("{destination} {source} {count}\n"));
+                source += count;
+            }
+        }
+
+        text
+    }
+
+    #[test]
+    fn day5_mapping_table_get_finds_mappings_in_large_tables() {
+        // Insert in a scrambled order so that the table has to keep itself sorted.
+        let count = 100_000_u64;
+        let mappings = (0..count)
+            .map(|i| (i * 7_919) % count)
+            .map(|i| Mapping::new(i * 1_000 + 500, i * 10, 5))
+            .collect::<Vec<_>>();
+        let map = MappingTable::try_from(mappings).expect("mapping table should be valid");
+
+        for i in (0..count).step_by(997) {
+            assert_eq!(map.get(i * 10 + 3), i * 1_000 + 503);
+            assert_eq!(map.get(i * 10 + 5), i * 10 + 5);
+        }
+    }
 }
 
 /// The Island Island Almanac
+#[derive(Default)]
 pub struct AlmanacBuilder {
     text: Option<String>,
+    json: Option<String>,
+    seed_ranges: bool,
+}
+
+/// The category that the seeds belong to.
+pub const SEED_CATEGORY: &str = "seed";
+
+/// The category that the puzzle asks for.
+pub const LOCATION_CATEGORY: &str = "location";
+
+// Separates the source and destination categories in a map's header, so category names can't contain it.
+const CATEGORY_SEPARATOR: &str = "-to-";
+
+// Whether a category name can be written in a map's header and read back as the same name.
+//
+// Besides the separator, a word ending in `:` would end the header early, a line break would split it, and whitespace
+// around the name would be mistaken for the space around the separator or before `map:`.
+fn is_valid_category_name(name: &str) -> bool {
+    !name.contains(CATEGORY_SEPARATOR)
+        && !name.contains(['\n', '\r'])
+        && name.trim() == name
+        && !name.split_whitespace().any(|word| word.ends_with(':'))
 }
 
 // The backing store for almanac data.
+//
+// Each map is an edge in a graph of categories, going from its source category to its destination category. Maps are
+// kept in the order they appear in the almanac text.
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct AlmanacDatabase {
-    seeds: HashSet<u64>,
-    seed_to_soil: Buoy<Vec<Mapping>>,
-    soil_to_fertilizer: Buoy<Vec<Mapping>>,
-    fertilizer_to_water: Buoy<Vec<Mapping>>,
-    water_to_light: Buoy<Vec<Mapping>>,
-    light_to_temperature: Buoy<Vec<Mapping>>,
-    temperature_to_humidity: Buoy<Vec<Mapping>>,
-    humidity_to_location: Buoy<Vec<Mapping>>,
-}
-
-/// Generic wrapper tuple struct for newtype pattern for the sake of being able to "buoy" externally defined
-/// types so that traits can be implemented for them.
-struct Buoy<T>(pub T);
-
-impl Buoy<Vec<Mapping>> {
-    fn get(&self, source_value: u64) -> u64 {
-        for mapping in &self.0 {
-            if source_value >= mapping.source && source_value <= mapping.source + mapping.count {
-                let offset = source_value - mapping.source;
-                return mapping.destination + offset;
-            }
-        }
-        source_value
+    seeds: Vec<u64>,
+    maps: Vec<CategoryMap>,
+}
+
+/// A map from values of one category to values of another, e.g. seed-to-soil.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryMap {
+    source: String,
+    destination: String,
+    table: MappingTable,
+}
+
+impl CategoryMap {
+    /// The category that the map takes values from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The category that the map gives values in.
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// The mappings of the map.
+    pub fn table(&self) -> &MappingTable {
+        &self.table
     }
 }
 
-struct Mapping {
+/// A mapping of a range of source values to a range of destination values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
     source: u64,
     destination: u64,
     count: u64,
 }
 
-impl From<&str> for AlmanacDatabase {
-    fn from(value: &str) -> Self {
-        let seeds = HashSet::new();
-        let seed_to_soil = Buoy(Vec::new());
-        let soil_to_fertilizer = Buoy(Vec::new());
-        let fertilizer_to_water = Buoy(Vec::new());
-        let water_to_light = Buoy(Vec::new());
-        let light_to_temperature = Buoy(Vec::new());
-        let temperature_to_humidity = Buoy(Vec::new());
-        let humidity_to_location = Buoy(Vec::new());
+impl Mapping {
+    /// Creates a new mapping, in the same order as an almanac line: destination, source, count.
+    pub fn new(destination: u64, source: u64, count: u64) -> Self {
+        Self {
+            source,
+            destination,
+            count,
+        }
+    }
 
-        let mut instance = Self {
-            seeds,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
-        };
+    /// The first source value of the mapping.
+    pub fn source(&self) -> u64 {
+        self.source
+    }
 
-        instance.seed(value);
+    /// The first destination value of the mapping.
+    pub fn destination(&self) -> u64 {
+        self.destination
+    }
 
-        instance
+    /// The number of values the mapping covers.
+    pub fn count(&self) -> u64 {
+        self.count
     }
-}
 
-impl AlmanacDatabase {
-    fn seed(&mut self, almanac_text: &str) {
+    /// The half-open range of source values covered by the mapping.
+    pub fn source_range(&self) -> Range<u64> {
+        self.source..self.source + self.count
+    }
+
+    /// The half-open range of destination values covered by the mapping.
+    pub fn destination_range(&self) -> Range<u64> {
+        self.destination..self.destination + self.count
+    }
+
+    fn check_overflow(&self) -> Result<(), MappingTableError> {
+        if self.source.checked_add(self.count).is_none()
+            || self.destination.checked_add(self.count).is_none()
         {
-            let mut current_section: Option<AlmanacSection> = None;
-            // Call to replace puts seed data on a new line to make it just like other data.
-            for line in almanac_text.replace("seeds:", "seeds:\r\n").lines() {
-                if line.trim().is_empty() {
+            return Err(MappingTableError::Overflow(*self));
+        }
+        Ok(())
+    }
+
+    fn contains(&self, source_value: u64) -> bool {
+        self.source_range().contains(&source_value)
+    }
+
+    /// How far the mapping moves a value: the destination minus the source.
+    pub fn offset(&self) -> i128 {
+        i128::from(self.destination) - i128::from(self.source)
+    }
+
+    fn map(&self, source_value: u64) -> u64 {
+        self.destination + (source_value - self.source)
+    }
+}
+
+/// A table of mappings, sorted by their source ranges.
+///
+/// Source ranges are half-open and never overlap, so lookups are a binary search. Mappings with a count of zero don't
+/// map anything; they are kept aside and never take part in lookups.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingTable {
+    mappings: Vec<Mapping>,
+    empty_mappings: Vec<Mapping>,
+}
+
+impl MappingTable {
+    /// Creates a new, empty mapping table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a mapping in sorted position.
+    ///
+    /// Fails if either range of the mapping doesn't fit in a u64, or if its source range overlaps that of a mapping
+    /// already in the table.
+    pub fn insert(&mut self, mapping: Mapping) -> Result<(), MappingTableError> {
+        mapping.check_overflow()?;
+
+        if mapping.count == 0 {
+            self.empty_mappings.push(mapping);
+            return Ok(());
+        }
+
+        let index = self
+            .mappings
+            .partition_point(|existing| existing.source < mapping.source);
+        let previous = index.checked_sub(1).and_then(|i| self.mappings.get(i));
+        let next = self.mappings.get(index);
+        let overlapping = previous
+            .filter(|previous| previous.source_range().end > mapping.source)
+            .or(next.filter(|next| mapping.source_range().end > next.source));
+        if let Some(existing) = overlapping {
+            return Err(MappingTableError::Overlap {
+                existing: *existing,
+                incoming: mapping,
+            });
+        }
+
+        self.mappings.insert(index, mapping);
+        Ok(())
+    }
+
+    /// The mappings that cover at least one value, sorted by source.
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    /// The mappings with a count of zero, in the order they were inserted.
+    pub fn empty_mappings(&self) -> &[Mapping] {
+        &self.empty_mappings
+    }
+
+    /// Finds the mapping whose source range contains the source value, if any.
+    pub fn find(&self, source_value: u64) -> Option<&Mapping> {
+        let index = self
+            .mappings
+            .partition_point(|mapping| mapping.source <= source_value);
+        index
+            .checked_sub(1)
+            .and_then(|i| self.mappings.get(i))
+            .filter(|mapping| mapping.contains(source_value))
+    }
+
+    /// Maps a source value to its destination value.
+    ///
+    /// Any source value that isn't mapped corresponds to the same destination value.
+    pub fn get(&self, source_value: u64) -> u64 {
+        match self.find(source_value) {
+            Some(mapping) => mapping.map(source_value),
+            None => source_value,
+        }
+    }
+
+    /// Maps whole ranges of source values to ranges of destination values, splitting them at mapping boundaries.
+    pub fn get_ranges(&self, source_ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        source_ranges
+            .iter()
+            .flat_map(|range| self.split(range.clone()))
+            .map(|(range, mapping)| match mapping {
+                Some(mapping) => mapping.map(range.start)..mapping.map(range.end),
+                // Any source values that aren't mapped correspond to the same destination values.
+                None => range,
+            })
+            .collect()
+    }
+
+    /// Combines this table with the table that follows it into a single table.
+    ///
+    /// Looking a value up in the combined table gives the same result as looking it up in this table and then looking
+    /// the result up in the next table. The combined table is normalized: it only holds mappings that actually move
+    /// values, and mappings that continue one another are merged.
+    pub fn compose(&self, next: &MappingTable) -> MappingTable {
+        let mut mappings: Vec<Mapping> = Vec::new();
+
+        for (range, mapping) in self.split(0..u64::MAX) {
+            let intermediate = match mapping {
+                Some(mapping) => mapping.map(range.start)..mapping.map(range.end),
+                None => range.clone(),
+            };
+
+            for (next_range, next_mapping) in next.split(intermediate.clone()) {
+                let source = range.start + (next_range.start - intermediate.start);
+                let destination = match next_mapping {
+                    Some(next_mapping) => next_mapping.map(next_range.start),
+                    None => next_range.start,
+                };
+                if source == destination {
                     continue;
                 }
 
-                let incoming = AlmanacTextParser::from(line);
+                let count = next_range.end - next_range.start;
+                match mappings.last_mut() {
+                    Some(last)
+                        if last.source_range().end == source
+                            && last.destination_range().end == destination =>
+                    {
+                        last.count += count;
+                    }
+                    _ => mappings.push(Mapping::new(destination, source, count)),
+                }
+            }
+        }
+
+        MappingTable {
+            mappings,
+            empty_mappings: Vec::new(),
+        }
+    }
+
+    /// Finds every range of source values that maps into the ranges of destination values.
+    ///
+    /// Several source ranges can map into the same destination values, e.g. a mapping that moves values onto a range
+    /// that is also left unmapped, so the result can cover more values than the destination ranges do.
+    pub fn get_source_ranges(&self, destination_ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        destination_ranges
+            .iter()
+            .flat_map(|range| self.invert(range.clone()))
+            .map(|(source_range, _)| source_range)
+            .collect()
+    }
+
+    /// Splits a range of destination values into the pieces of source values that map into it.
+    ///
+    /// Each piece pairs a range of source values with the range of destination values it maps to.
+    fn invert(&self, range: Range<u64>) -> Vec<(Range<u64>, Range<u64>)> {
+        let mapped = self.mappings.iter().filter_map(|mapping| {
+            let destination_range = mapping.destination_range();
+            let start = range.start.max(destination_range.start);
+            let end = range.end.min(destination_range.end);
+            (start < end).then(|| {
+                let source_start = mapping.source + (start - mapping.destination);
+                let source_end = mapping.source + (end - mapping.destination);
+                (source_start..source_end, start..end)
+            })
+        });
+
+        // Any destination values that aren't the source of a mapping came from the same source values.
+        let unmapped = self
+            .split(range.clone())
+            .into_iter()
+            .filter(|(_, mapping)| mapping.is_none())
+            .map(|(range, _)| (range.clone(), range));
 
-                match (&current_section, incoming) {
+        mapped.chain(unmapped).collect()
+    }
+
+    /// Splits a range of source values into consecutive pieces that are either covered by a single mapping or not
+    /// mapped at all.
+    fn split(&self, range: Range<u64>) -> Vec<(Range<u64>, Option<&Mapping>)> {
+        let mut pieces = Vec::new();
+        if range.is_empty() {
+            return pieces;
+        }
+
+        let first = self
+            .mappings
+            .partition_point(|mapping| mapping.source_range().end <= range.start);
+        let mut start = range.start;
+
+        for mapping in self.mappings[first..]
+            .iter()
+            .take_while(|mapping| mapping.source < range.end)
+        {
+            if start < mapping.source {
+                pieces.push((start..mapping.source, None));
+                start = mapping.source;
+            }
+
+            let end = range.end.min(mapping.source_range().end);
+            pieces.push((start..end, Some(mapping)));
+            start = end;
+        }
+
+        if start < range.end {
+            pieces.push((start..range.end, None));
+        }
+
+        pieces
+    }
+}
+
+impl TryFrom<Vec<Mapping>> for MappingTable {
+    type Error = MappingTableError;
+
+    /// Builds a table from mappings in any order, sorting them once rather than inserting them one at a time.
+    ///
+    /// Fails like [`MappingTable::insert`] would, where the later of two overlapping mappings is the incoming one.
+    fn try_from(value: Vec<Mapping>) -> Result<Self, Self::Error> {
+        Self::from_unsorted(value).map_err(|(_, e)| e)
+    }
+}
+
+impl MappingTable {
+    // Builds a table from mappings in any order. An error comes with the index of the mapping it's about, so that
+    // callers can point at where that mapping came from.
+    fn from_unsorted(mappings: Vec<Mapping>) -> Result<Self, (usize, MappingTableError)> {
+        let mut sorted = Vec::with_capacity(mappings.len());
+        let mut empty_mappings = Vec::new();
+        for (index, mapping) in mappings.into_iter().enumerate() {
+            mapping.check_overflow().map_err(|e| (index, e))?;
+            if mapping.count == 0 {
+                empty_mappings.push(mapping);
+            } else {
+                sorted.push((index, mapping));
+            }
+        }
+        sorted.sort_unstable_by_key(|(index, mapping)| (mapping.source, *index));
+
+        // Once sorted, any overlap includes an overlap between neighbors.
+        let overlap = sorted
+            .windows(2)
+            .find(|pair| pair[0].1.source_range().end > pair[1].1.source);
+        if let Some(&[first, second]) = overlap {
+            let (existing, incoming) = if first.0 < second.0 {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            return Err((
+                incoming.0,
+                MappingTableError::Overlap {
+                    existing: existing.1,
+                    incoming: incoming.1,
+                },
+            ));
+        }
+
+        Ok(Self {
+            mappings: sorted.into_iter().map(|(_, mapping)| mapping).collect(),
+            empty_mappings,
+        })
+    }
+}
+
+/// Errors when inserting mappings into a mapping table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingTableError {
+    /// The source or destination range of the mapping goes past the largest u64.
+    Overflow(Mapping),
+
+    /// The source range of the incoming mapping overlaps that of an existing mapping.
+    Overlap {
+        /// The mapping that was already in the table.
+        existing: Mapping,
+
+        /// The mapping that was being inserted.
+        incoming: Mapping,
+    },
+}
+
+impl Display for MappingTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow(mapping) => write!(
+                f,
+                "mapping {} {} {} goes past the largest possible value",
+                mapping.destination, mapping.source, mapping.count
+            ),
+            Self::Overlap { existing, incoming } => write!(
+                f,
+                "mapping {} {} {} overlaps mapping {} {} {}",
+                incoming.destination,
+                incoming.source,
+                incoming.count,
+                existing.destination,
+                existing.source,
+                existing.count
+            ),
+        }
+    }
+}
+
+impl Error for MappingTableError {}
+
+// Almanac text that has been parsed, but whose maps haven't been checked for overlapping mappings yet.
+//
+// Each row remembers where it came from, so that problems found later can still point at the text.
+struct AlmanacDraft {
+    seeds: Vec<u64>,
+    seed_positions: Vec<DraftSeed>,
+    maps: Vec<DraftMap>,
+}
+
+// Where a seed number came from, for pointing at it if it doesn't make a valid range.
+struct DraftSeed {
+    line: usize,
+    column: usize,
+    text: String,
+}
+
+struct DraftMap {
+    source: String,
+    destination: String,
+    rows: Vec<DraftRow>,
+}
+
+struct DraftRow {
+    line: usize,
+    column: usize,
+    text: String,
+    mapping: Mapping,
+}
+
+impl DraftRow {
+    fn error(&self, kind: AlmanacParseErrorKind) -> AlmanacParseError {
+        AlmanacParseError {
+            line: self.line,
+            column: self.column,
+            text: self.text.clone(),
+            kind,
+        }
+    }
+}
+
+impl TryFrom<&str> for AlmanacDraft {
+    type Error = AlmanacParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut instance = Self {
+            seeds: Vec::new(),
+            seed_positions: Vec::new(),
+            maps: Vec::new(),
+        };
+
+        instance.seed(value)?;
+
+        Ok(instance)
+    }
+}
+
+impl AlmanacDraft {
+    // Checks that the seeds come in pairs of range start and length.
+    fn check_seed_ranges(&self) -> Result<(), AlmanacParseError> {
+        match self.seed_positions.last() {
+            Some(seed) if self.seeds.len() % 2 == 1 => Err(AlmanacParseError {
+                line: seed.line,
+                column: seed.column,
+                text: seed.text.clone(),
+                kind: AlmanacParseErrorKind::UnpairedSeed,
+            }),
+            _ => self.check_seed_range_overflow(),
+        }
+    }
+
+    // Checks that no pair of seeds, read as a range start and length, runs past `u64::MAX`.
+    fn check_seed_range_overflow(&self) -> Result<(), AlmanacParseError> {
+        let overflowing = self
+            .seeds
+            .chunks_exact(2)
+            .zip(self.seed_positions.chunks_exact(2))
+            .find(|(pair, _)| pair[0].checked_add(pair[1]).is_none());
+        match overflowing {
+            Some((pair, positions)) => Err(AlmanacParseError {
+                line: positions[0].line,
+                column: positions[0].column,
+                text: format!("{} {}", pair[0], pair[1]),
+                kind: AlmanacParseErrorKind::SeedRangeOverflow,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn seed(&mut self, almanac_text: &str) -> Result<(), AlmanacParseError> {
+        let mut current_section: Option<AlmanacSection> = None;
+        let mut seen_sections: Vec<AlmanacSection> = Vec::new();
+
+        for (index, line) in almanac_text.lines().enumerate() {
+            for token in AlmanacTextParser::parse_line(line, index + 1)? {
+                match (&current_section, &token.parsed) {
                     (_, AlmanacTextParser::Section(new_section)) => {
-                        current_section = Some(new_section);
+                        if seen_sections.contains(new_section) {
+                            return Err(token.error(AlmanacParseErrorKind::DuplicateSection));
+                        }
+                        // A map without any mappings still connects its categories.
+                        if let AlmanacSection::Map {
+                            source,
+                            destination,
+                        } = new_section
+                        {
+                            self.maps.push(DraftMap {
+                                source: source.clone(),
+                                destination: destination.clone(),
+                                rows: Vec::new(),
+                            });
+                        }
+                        seen_sections.push(new_section.clone());
+                        current_section = Some(new_section.clone());
                     }
                     (Some(section), AlmanacTextParser::Data(data)) => {
-                        self.seed_section(section, &data);
+                        self.seed_section(section, data, &token)?;
+                    }
+                    (None, AlmanacTextParser::Data(_)) => {
+                        return Err(token.error(AlmanacParseErrorKind::DataBeforeHeader));
                     }
-                    (a, b) => panic!("Unexpected parser state: ({a:#?}, {b:#?})"),
                 };
             }
         }
+        Ok(())
     }
 
-    fn seed_section(&mut self, section: &AlmanacSection, numbers: &Vec<u64>) {
+    fn seed_section(
+        &mut self,
+        section: &AlmanacSection,
+        numbers: &[u64],
+        token: &AlmanacToken,
+    ) -> Result<(), AlmanacParseError> {
         match section {
             AlmanacSection::Seeds => {
-                numbers.iter().for_each(|n| {
-                    self.seeds.insert(*n);
-                });
+                self.seeds.extend_from_slice(numbers);
+                self.seed_positions
+                    .extend(split_words(token.text).into_iter().map(|word| DraftSeed {
+                        line: token.line,
+                        column: token.column + word.column - 1,
+                        text: word.text.to_owned(),
+                    }));
             }
-            map_type => {
-                if numbers.len() < 3 {
-                    panic!("numbers should have at least 3 elements!");
-                }
-                let map = match &map_type {
-                    AlmanacSection::Seeds => panic!("impossibru!"),
-                    AlmanacSection::SeedToSoil => &mut self.seed_to_soil,
-                    AlmanacSection::SoilToFertilizer => &mut self.soil_to_fertilizer,
-                    AlmanacSection::FertilizerToWater => &mut self.fertilizer_to_water,
-                    AlmanacSection::WaterToLight => &mut self.water_to_light,
-                    AlmanacSection::LightToTemperature => &mut self.light_to_temperature,
-                    AlmanacSection::TemperatureToHumidity => &mut self.temperature_to_humidity,
-                    AlmanacSection::HumidityToLocation => &mut self.humidity_to_location,
+            AlmanacSection::Map { .. } => {
+                let &[destination, source, count] = numbers else {
+                    return Err(token.error(AlmanacParseErrorKind::WrongNumberOfValues {
+                        expected: 3,
+                        found: numbers.len(),
+                    }));
                 };
-                let source = *numbers.get(1).expect("unable to get numbers[1]");
-                let destination = *numbers.get(0).expect("unable to get numbers[0]");
-                let count = *numbers.get(2).expect("unable to get numbers[2]");
-                map.0.push(Mapping {
-                    source,
-                    destination,
-                    count,
+                let mapping = Mapping::new(destination, source, count);
+                mapping
+                    .check_overflow()
+                    .map_err(|e| token.error(AlmanacParseErrorKind::Mapping(e)))?;
+                let map = self
+                    .maps
+                    .last_mut()
+                    .expect("A map section should have a map");
+                map.rows.push(DraftRow {
+                    line: token.line,
+                    column: token.column,
+                    text: token.text.to_owned(),
+                    mapping,
                 });
             }
         }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for AlmanacDatabase {
+    type Error = AlmanacParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(AlmanacDraft::try_from(value)?)
+    }
+}
+
+impl TryFrom<AlmanacDraft> for AlmanacDatabase {
+    type Error = AlmanacParseError;
+
+    fn try_from(value: AlmanacDraft) -> Result<Self, Self::Error> {
+        let maps = value
+            .maps
+            .into_iter()
+            .map(|map| {
+                let mappings = map.rows.iter().map(|row| row.mapping).collect();
+                let table = MappingTable::from_unsorted(mappings).map_err(|(index, e)| {
+                    map.rows[index].error(AlmanacParseErrorKind::Mapping(e))
+                })?;
+                Ok(CategoryMap {
+                    source: map.source,
+                    destination: map.destination,
+                    table,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            seeds: value.seeds,
+            maps,
+        })
     }
 }
 
-impl Default for AlmanacBuilder {
-    fn default() -> Self {
-        Self { text: None }
+impl AlmanacDatabase {
+    /// Finds the shortest chain of maps that leads from one category to another.
+    ///
+    /// The chain is empty when both categories are the same.
+    fn find_path(&self, from: &str, to: &str) -> Result<Vec<&CategoryMap>, AlmanacError> {
+        // Breadth first search, remembering which map was used to reach each category.
+        let mut reached_by: HashMap<&str, Option<&CategoryMap>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = category;
+                while let Some(Some(map)) = reached_by.get(current) {
+                    path.push(*map);
+                    current = &map.source;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+
+            for map in self.maps.iter().filter(|map| map.source == category) {
+                if !reached_by.contains_key(map.destination.as_str()) {
+                    reached_by.insert(&map.destination, Some(map));
+                    queue.push_back(&map.destination);
+                }
+            }
+        }
+
+        Err(AlmanacError::NoPath {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        })
     }
 }
+
 impl AlmanacBuilder {
     /// Creates a new instance of an AlamancBuilder.
     pub fn new() -> Self {
@@ -192,21 +1299,56 @@ impl AlmanacBuilder {
     }
 
     /// Sets the text for the AlmanacBuilder to parse when it builds.
+    ///
+    /// Replaces any JSON set with [`AlmanacBuilder::json`].
     pub fn text(&mut self, text: impl Into<String>) -> &mut Self {
         self.text = Some(text.into());
+        self.json = None;
+        self
+    }
+
+    /// Sets JSON, as written by [`Almanac::to_json`], for the AlmanacBuilder to parse when it builds.
+    ///
+    /// Replaces any text set with [`AlmanacBuilder::text`].
+    pub fn json(&mut self, json: impl Into<String>) -> &mut Self {
+        self.json = Some(json.into());
+        self.text = None;
         self
     }
 
-    /// Builds an Almanac from the text.
+    /// Sets whether the seeds must come in pairs of range start and length, as they do when read as seed ranges.
+    ///
+    /// When set, a trailing seed number without a length fails the build instead of failing
+    /// [`Almanac::get_initial_seed_ranges`] later on.
+    pub fn seed_ranges(&mut self, seed_ranges: bool) -> &mut Self {
+        self.seed_ranges = seed_ranges;
+        self
+    }
+
+    /// Builds an Almanac from the text or JSON.
+    ///
+    /// Any problem with the input is reported with the line and column where it was found.
     pub fn build(&self) -> Result<Almanac, AlmanacBuilderError> {
-        let Some(text) = self.text.as_ref() else {
-            return Err(AlmanacBuilderError::NoText);
-        };
+        let draft = self.draft()?;
+        if self.seed_ranges {
+            draft
+                .check_seed_ranges()
+                .map_err(AlmanacBuilderError::Parse)?;
+        }
 
-        let db = AlmanacDatabase::from(text.as_str());
+        let db = AlmanacDatabase::try_from(draft).map_err(AlmanacBuilderError::Parse)?;
 
         Ok(Almanac { db })
     }
+
+    fn draft(&self) -> Result<AlmanacDraft, AlmanacBuilderError> {
+        let draft = match (&self.text, &self.json) {
+            (Some(text), _) => AlmanacDraft::try_from(text.as_str()),
+            (None, Some(json)) => AlmanacDraft::from_json(json),
+            (None, None) => return Err(AlmanacBuilderError::NoText),
+        };
+        draft.map_err(AlmanacBuilderError::Parse)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -215,75 +1357,512 @@ enum AlmanacTextParser {
     Data(Vec<u64>),
 }
 
-impl From<&str> for AlmanacTextParser {
-    fn from(value: &str) -> Self {
-        let text = value.trim();
-        match text {
-            "seeds:" => Self::Section(AlmanacSection::Seeds),
-            "seed-to-soil map:" => Self::Section(AlmanacSection::SeedToSoil),
-            "soil-to-fertilizer map:" => Self::Section(AlmanacSection::SoilToFertilizer),
-            "fertilizer-to-water map:" => Self::Section(AlmanacSection::FertilizerToWater),
-            "water-to-light map:" => Self::Section(AlmanacSection::WaterToLight),
-            "light-to-temperature map:" => Self::Section(AlmanacSection::LightToTemperature),
-            "temperature-to-humidity map:" => Self::Section(AlmanacSection::TemperatureToHumidity),
-            "humidity-to-location map:" => Self::Section(AlmanacSection::HumidityToLocation),
-            t => Self::Data(
-                t.split_whitespace()
-                    .map(|n| n.parse::<u64>().expect("Error parsing numbers"))
-                    .collect::<Vec<_>>(),
-            ),
+// A parsed piece of a line of almanac text, along with where it was found.
+struct AlmanacToken<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+    parsed: AlmanacTextParser,
+}
+
+impl AlmanacToken<'_> {
+    fn error(&self, kind: AlmanacParseErrorKind) -> AlmanacParseError {
+        AlmanacParseError {
+            line: self.line,
+            column: self.column,
+            text: self.text.to_owned(),
+            kind,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl AlmanacTextParser {
+    /// Parses a line of almanac text.
+    ///
+    /// A line can hold a section header, data, or both, like the seeds line does. Blank lines hold nothing.
+    fn parse_line(
+        line: &str,
+        line_number: usize,
+    ) -> Result<Vec<AlmanacToken<'_>>, AlmanacParseError> {
+        let words = split_words(line);
+        let header_length = words
+            .iter()
+            .position(|word| word.text.ends_with(':'))
+            .map_or(0, |i| i + 1);
+        let (header_words, data_words) = words.split_at(header_length);
+        let mut tokens = Vec::new();
+
+        if let (Some(first), Some(last)) = (header_words.first(), header_words.last()) {
+            let text = &line[first.offset..last.offset + last.text.len()];
+            let map_header = text
+                .strip_suffix(" map:")
+                .and_then(|categories| categories.split_once(CATEGORY_SEPARATOR));
+            let section = match (text, map_header) {
+                ("seeds:", _) => AlmanacSection::Seeds,
+                (_, Some((source, destination)))
+                    if !is_valid_category_name(source) || !is_valid_category_name(destination) =>
+                {
+                    return Err(AlmanacParseError {
+                        line: line_number,
+                        column: first.column,
+                        text: text.to_owned(),
+                        kind: AlmanacParseErrorKind::CategoryName,
+                    })
+                }
+                (_, Some((source, destination))) => AlmanacSection::Map {
+                    source: source.to_owned(),
+                    destination: destination.to_owned(),
+                },
+                (_, None) => {
+                    return Err(AlmanacParseError {
+                        line: line_number,
+                        column: first.column,
+                        text: text.to_owned(),
+                        kind: AlmanacParseErrorKind::UnknownHeader,
+                    })
+                }
+            };
+            tokens.push(AlmanacToken {
+                line: line_number,
+                column: first.column,
+                text,
+                parsed: Self::Section(section),
+            });
+        }
+
+        if let (Some(first), Some(last)) = (data_words.first(), data_words.last()) {
+            let numbers = data_words
+                .iter()
+                .map(|word| {
+                    word.text.parse::<u64>().map_err(|e| AlmanacParseError {
+                        line: line_number,
+                        column: word.column,
+                        text: word.text.to_owned(),
+                        kind: AlmanacParseErrorKind::InvalidNumber(e),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            tokens.push(AlmanacToken {
+                line: line_number,
+                column: first.column,
+                text: &line[first.offset..last.offset + last.text.len()],
+                parsed: Self::Data(numbers),
+            });
+        }
+
+        Ok(tokens)
+    }
+}
+
+// A whitespace separated word within a line of text.
+struct Word<'a> {
+    // Byte offset of the word within the line.
+    offset: usize,
+    // 1-based column of the first character of the word.
+    column: usize,
+    text: &'a str,
+}
+
+fn split_words(line: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+
+    for (column, (offset, c)) in (1..).zip(line.char_indices()) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((offset, column)),
+            (true, Some((word_offset, word_column))) => {
+                words.push(Word {
+                    offset: word_offset,
+                    column: word_column,
+                    text: &line[word_offset..offset],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((offset, column)) = start {
+        words.push(Word {
+            offset,
+            column,
+            text: &line[offset..],
+        });
+    }
+
+    words
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum AlmanacSection {
     Seeds,
-    SeedToSoil,
-    SoilToFertilizer,
-    FertilizerToWater,
-    WaterToLight,
-    LightToTemperature,
-    TemperatureToHumidity,
-    HumidityToLocation,
+    Map { source: String, destination: String },
 }
 
 /// Errors when building the almanac.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlmanacBuilderError {
-    /// No text in the input.
+    /// No text or JSON in the input.
     NoText,
 
-    /// A database error occurred.
-    Database(String),
+    /// The text could not be parsed.
+    Parse(AlmanacParseError),
 }
 
+impl Display for AlmanacBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoText => write!(f, "no almanac text or JSON to build from"),
+            Self::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for AlmanacBuilderError {}
+
+/// A problem in the almanac text, along with where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlmanacParseError {
+    /// The 1-based line number of the problem.
+    pub line: usize,
+
+    /// The 1-based column where the offending text starts.
+    pub column: usize,
+
+    /// The offending text.
+    pub text: String,
+
+    /// What the problem is.
+    pub kind: AlmanacParseErrorKind,
+}
+
+impl Display for AlmanacParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {} in '{}'",
+            self.line, self.column, self.kind, self.text
+        )
+    }
+}
+
+impl Error for AlmanacParseError {}
+
+/// The kinds of problems in almanac text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlmanacParseErrorKind {
+    /// A value is not a number.
+    InvalidNumber(ParseIntError),
+
+    /// Data appears before any section header.
+    DataBeforeHeader,
+
+    /// A row of a map does not have the right number of values.
+    WrongNumberOfValues {
+        /// How many values a row should have.
+        expected: usize,
+
+        /// How many values the row has.
+        found: usize,
+    },
+
+    /// A header is neither `seeds:` nor of the form `X-to-Y map:`.
+    UnknownHeader,
+
+    /// A section appears more than once.
+    DuplicateSection,
+
+    /// A mapping could not be added to its map.
+    Mapping(MappingTableError),
+
+    /// JSON input is malformed or isn't shaped like an almanac.
+    Json(String),
+
+    /// The last seed number has no range length to go with it.
+    UnpairedSeed,
+
+    /// A seed range runs past `u64::MAX`.
+    SeedRangeOverflow,
+
+    /// A category name can't be written in a map's header and read back: it contains `-to-`, a word ending in `:` or
+    /// a line break, or it starts or ends with whitespace.
+    CategoryName,
+}
+
+impl Display for AlmanacParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(e) => write!(f, "invalid number ({e})"),
+            Self::DataBeforeHeader => write!(f, "data before any section header"),
+            Self::WrongNumberOfValues { expected, found } => {
+                write!(f, "expected {expected} values but found {found}")
+            }
+            Self::UnknownHeader => write!(f, "unknown section header"),
+            Self::DuplicateSection => write!(f, "duplicate section"),
+            Self::Mapping(e) => write!(f, "{e}"),
+            Self::Json(message) => write!(f, "{message}"),
+            Self::UnpairedSeed => write!(f, "seed range without a length"),
+            Self::SeedRangeOverflow => write!(f, "seed range runs past {}", u64::MAX),
+            Self::CategoryName => write!(
+                f,
+                "category name containing '-to-', a word ending in ':', a line break or surrounding whitespace"
+            ),
+        }
+    }
+}
+
+/// Errors when using the almanac.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlmanacError {
+    /// No chain of maps leads from one category to the other.
+    NoPath {
+        /// The category being mapped from.
+        from: String,
+
+        /// The category being mapped to.
+        to: String,
+    },
+
+    /// There are no seeds, or no seeds within the seed ranges, to find a location for.
+    NoSeeds,
+
+    /// The seeds can't be read as ranges because the last seed number has no length to go with it.
+    UnpairedSeed,
+
+    /// A seed range runs past `u64::MAX`.
+    SeedRangeOverflow {
+        /// The first seed of the range.
+        start: u64,
+
+        /// The length of the range.
+        length: u64,
+    },
+
+    /// The value is `u64::MAX`, which no mapping covers and a half-open range can't include.
+    UnrepresentableValue(u64),
+}
+
+impl Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPath { from, to } => write!(f, "no chain of maps leads from {from} to {to}"),
+            Self::NoSeeds => write!(f, "no seeds to find a location for"),
+            Self::UnpairedSeed => {
+                write!(
+                    f,
+                    "an odd number of seed numbers can't be read as seed ranges"
+                )
+            }
+            Self::SeedRangeOverflow { start, length } => {
+                write!(f, "seed range {start} {length} runs past {}", u64::MAX)
+            }
+            Self::UnrepresentableValue(value) => {
+                write!(
+                    f,
+                    "{value} only corresponds to itself, which a range can't include"
+                )
+            }
+        }
+    }
+}
+
+impl Error for AlmanacError {}
+
 /// The Island Island Almanac
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Almanac {
     db: AlmanacDatabase,
 }
 
 impl Almanac {
-    fn get_lowest_location_number_that_corresponds_to_any_initial_seed_number(&self) -> u64 {
-        let db = &self.db;
-        let mut locations = db
+    /// Gets the initial seeds interpreted as ranges.
+    ///
+    /// The seed numbers come in pairs: the first value of each pair is the start of the range and the second value is
+    /// the length of the range. A trailing seed number without a length, or a range running past `u64::MAX`, is an
+    /// error.
+    pub fn get_initial_seed_ranges(&self) -> Result<Vec<Range<u64>>, AlmanacError> {
+        if self.db.seeds.len() % 2 == 1 {
+            return Err(AlmanacError::UnpairedSeed);
+        }
+        let mut ranges = Vec::new();
+        for pair in self.db.seeds.chunks_exact(2) {
+            let (start, length) = (pair[0], pair[1]);
+            let end = start
+                .checked_add(length)
+                .ok_or(AlmanacError::SeedRangeOverflow { start, length })?;
+            if length > 0 {
+                ranges.push(start..end);
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// The maps in the almanac, in the order they appear in the text.
+    pub fn maps(&self) -> &[CategoryMap] {
+        &self.db.maps
+    }
+
+    /// Maps a value from one category to another, following the shortest chain of maps between them.
+    pub fn map_value(&self, value: u64, from: &str, to: &str) -> Result<u64, AlmanacError> {
+        let path = self.db.find_path(from, to)?;
+        Ok(path.iter().fold(value, |value, map| map.table.get(value)))
+    }
+
+    /// Combines the chain of maps from one category to another into a single table.
+    ///
+    /// Each value can then be mapped with a single lookup instead of one lookup per map in the chain.
+    pub fn compose(&self, from: &str, to: &str) -> Result<MappingTable, AlmanacError> {
+        let path = self.db.find_path(from, to)?;
+        Ok(path.iter().fold(MappingTable::new(), |composed, map| {
+            composed.compose(&map.table)
+        }))
+    }
+
+    /// Maps ranges of values from one category to another, following the shortest chain of maps between them.
+    pub fn map_ranges(
+        &self,
+        ranges: &[Range<u64>],
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Range<u64>>, AlmanacError> {
+        let path = self.db.find_path(from, to)?;
+        Ok(path
+            .iter()
+            .fold(ranges.to_vec(), |ranges, map| map.table.get_ranges(&ranges)))
+    }
+
+    /// Finds every range of values in one category that maps into the given ranges of another category.
+    ///
+    /// This is the inverse of [`Almanac::map_ranges`]: each map in the chain between the categories is undone in
+    /// reverse order. The resulting ranges are sorted and merged where they touch.
+    pub fn unmap_ranges(
+        &self,
+        ranges: &[Range<u64>],
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Range<u64>>, AlmanacError> {
+        let path = self.db.find_path(from, to)?;
+        let ranges = path.iter().rev().fold(ranges.to_vec(), |ranges, map| {
+            map.table.get_source_ranges(&ranges)
+        });
+        Ok(merge_ranges(ranges))
+    }
+
+    /// Finds every range of seeds that corresponds to the location.
+    ///
+    /// The location `u64::MAX` is an error: no mapping covers it, so only the seed `u64::MAX` corresponds to it, and
+    /// that seed can't be held in a range.
+    pub fn get_seed_ranges_for_location(
+        &self,
+        location: u64,
+    ) -> Result<Vec<Range<u64>>, AlmanacError> {
+        let Some(end) = location.checked_add(1) else {
+            self.db.find_path(SEED_CATEGORY, LOCATION_CATEGORY)?;
+            return Err(AlmanacError::UnrepresentableValue(location));
+        };
+        self.get_seed_ranges_for_location_range(location..end)
+    }
+
+    /// Finds every range of seeds that corresponds to any location within the range.
+    pub fn get_seed_ranges_for_location_range(
+        &self,
+        locations: Range<u64>,
+    ) -> Result<Vec<Range<u64>>, AlmanacError> {
+        self.unmap_ranges(&[locations], SEED_CATEGORY, LOCATION_CATEGORY)
+    }
+
+    /// Gets the lowest location number that corresponds to any of the initial seed numbers.
+    pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_number(
+        &self,
+    ) -> Result<u64, AlmanacError> {
+        let seed_to_location = self.compose(SEED_CATEGORY, LOCATION_CATEGORY)?;
+        let location = self
+            .db
             .seeds
             .iter()
-            .map(|seed| db.seed_to_soil.get(*seed))
-            .map(|soil| db.soil_to_fertilizer.get(soil))
-            .map(|fertilizer| db.fertilizer_to_water.get(fertilizer))
-            .map(|water| db.water_to_light.get(water))
-            .map(|light| db.light_to_temperature.get(light))
-            .map(|temperature| db.temperature_to_humidity.get(temperature))
-            .map(|humidity| db.humidity_to_location.get(humidity))
-            .collect::<Vec<_>>();
-        locations.sort_unstable();
-        *locations
-            .first()
-            .expect("There should be at least one location!")
+            .map(|seed| seed_to_location.get(*seed))
+            .min()
+            .ok_or(AlmanacError::NoSeeds)?;
+        Ok(location)
+    }
+
+    /// Gets the lowest location number that corresponds to any seed number within the initial seed ranges.
+    ///
+    /// Whole ranges are mapped through each layer of the almanac rather than individual seeds, so the amount of work
+    /// depends on the number of ranges and mappings, not on the number of seeds.
+    pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_range(
+        &self,
+    ) -> Result<u64, AlmanacError> {
+        let seeds = self.get_initial_seed_ranges()?;
+        let locations = self.map_ranges(&seeds, SEED_CATEGORY, LOCATION_CATEGORY)?;
+        let location = locations
+            .iter()
+            .map(|range| range.start)
+            .min()
+            .ok_or(AlmanacError::NoSeeds)?;
+        Ok(location)
+    }
+
+    /// Gets the lowest location number that corresponds to any seed number within the initial seed ranges, working
+    /// backwards from the locations.
+    ///
+    /// This doesn't share any of the forward mapping logic, so it can be used to cross-check
+    /// [`Almanac::get_lowest_location_number_that_corresponds_to_any_initial_seed_range`].
+    pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_range_in_reverse(
+        &self,
+    ) -> Result<u64, AlmanacError> {
+        let path = self.db.find_path(SEED_CATEGORY, LOCATION_CATEGORY)?;
+
+        // Pieces of every category along the way, each paired with the range of locations it maps to.
+        let pieces = path
+            .iter()
+            .rev()
+            .fold(vec![(0..u64::MAX, 0..u64::MAX)], |pieces, map| {
+                pieces
+                    .into_iter()
+                    .flat_map(|(range, locations)| {
+                        map.table.invert(range.clone()).into_iter().map(
+                            move |(source_range, destination_range)| {
+                                let start =
+                                    locations.start + (destination_range.start - range.start);
+                                let end = start + (destination_range.end - destination_range.start);
+                                (source_range, start..end)
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        let seeds = self.get_initial_seed_ranges()?;
+        let location = pieces
+            .iter()
+            .flat_map(|(range, locations)| {
+                seeds.iter().filter_map(move |seed_range| {
+                    let start = range.start.max(seed_range.start);
+                    let end = range.end.min(seed_range.end);
+                    (start < end).then(|| locations.start + (start - range.start))
+                })
+            })
+            .min()
+            .ok_or(AlmanacError::NoSeeds)?;
+        Ok(location)
     }
 }
 
+/// Sorts ranges and merges the ones that overlap or touch.
+fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<u64>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
 /// Gets the lowest location number that corresonds to any initial seed number.
 pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_number(
     almanac_text: &str,
@@ -293,5 +1872,34 @@ pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_number(
         .build()
         .expect("Unable to build almanac");
 
-    almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_number()
+    almanac
+        .get_lowest_location_number_that_corresponds_to_any_initial_seed_number()
+        .expect("Unable to map seeds to locations")
+}
+
+/// Gets the lowest location number that corresponds to any seed number within the initial seed ranges.
+pub fn get_lowest_location_number_that_corresponds_to_any_initial_seed_range(
+    almanac_text: &str,
+) -> u64 {
+    let almanac = AlmanacBuilder::new()
+        .text(almanac_text)
+        .seed_ranges(true)
+        .build()
+        .expect("Unable to build almanac");
+
+    almanac
+        .get_lowest_location_number_that_corresponds_to_any_initial_seed_range()
+        .expect("Unable to map seeds to locations")
+}
+
+/// Traces a seed through each map on the way to its location.
+pub fn trace_seed(almanac_text: &str, seed: u64) -> Trace {
+    let almanac = AlmanacBuilder::new()
+        .text(almanac_text)
+        .build()
+        .expect("Unable to build almanac");
+
+    almanac
+        .trace(seed)
+        .expect("Unable to map seeds to locations")
 }
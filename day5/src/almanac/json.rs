@@ -0,0 +1,341 @@
+//! Just enough JSON reading and writing for almanacs and traces.
+
+use super::{AlmanacParseError, AlmanacParseErrorKind};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn day5_parse_json_reads_nested_values_with_their_positions() {
+        let value = parse_json("{\n  \"a\": [1, true, null],\n  \"b\": \"x\\\"\\u00e9\"\n}")
+            .expect("json should parse");
+
+        let JsonKind::Object(members) = &value.kind else {
+            panic!("expected an object but got {value:?}");
+        };
+        assert_eq!(members[0].0, "a");
+        assert_eq!((members[0].1.line, members[0].1.column), (2, 8));
+        assert!(matches!(&members[0].1.kind, JsonKind::Array(items) if items.len() == 3));
+        assert_eq!(members[1].1.kind, JsonKind::String("x\"é".to_owned()));
+    }
+
+    #[test]
+    fn day5_parse_json_reports_where_the_syntax_is_wrong() {
+        let error = parse_json("[1,\n 2 3]").expect_err("json should not parse");
+
+        assert_eq!((error.line, error.column), (2, 4));
+        assert_eq!(error.text, "3");
+    }
+}
+
+/// A JSON value along with where it starts in the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct JsonValue {
+    pub line: usize,
+    pub column: usize,
+    pub kind: JsonKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum JsonKind {
+    Null,
+    Bool(bool),
+    // Numbers are kept as text, so that the reader decides what kind of number it expects.
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    // Members are kept in the order they appear in the text.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Creates an error pointing at this value.
+    pub fn error(&self, message: impl Into<String>) -> AlmanacParseError {
+        let text = match &self.kind {
+            JsonKind::Null => "null".to_owned(),
+            JsonKind::Bool(b) => b.to_string(),
+            JsonKind::Number(n) => n.clone(),
+            JsonKind::String(s) => format!("\"{s}\""),
+            JsonKind::Array(_) => "[".to_owned(),
+            JsonKind::Object(_) => "{".to_owned(),
+        };
+        AlmanacParseError {
+            line: self.line,
+            column: self.column,
+            text,
+            kind: AlmanacParseErrorKind::Json(message.into()),
+        }
+    }
+
+    /// Gets a member of an object, failing if this isn't an object or the member is missing.
+    pub fn member(&self, name: &str) -> Result<&JsonValue, AlmanacParseError> {
+        let JsonKind::Object(members) = &self.kind else {
+            return Err(self.error("expected an object"));
+        };
+        members
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| self.error(format!("missing member '{name}'")))
+    }
+
+    pub fn as_array(&self) -> Result<&[JsonValue], AlmanacParseError> {
+        match &self.kind {
+            JsonKind::Array(items) => Ok(items),
+            _ => Err(self.error("expected an array")),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, AlmanacParseError> {
+        match &self.kind {
+            JsonKind::String(s) => Ok(s),
+            _ => Err(self.error("expected a string")),
+        }
+    }
+
+    pub fn as_u64(&self) -> Result<u64, AlmanacParseError> {
+        match &self.kind {
+            JsonKind::Number(n) => n
+                .parse::<u64>()
+                .map_err(|_| self.error("expected a whole number that fits in a u64")),
+            _ => Err(self.error("expected a number")),
+        }
+    }
+}
+
+/// Parses JSON text into a value.
+pub(super) fn parse_json(text: &str) -> Result<JsonValue, AlmanacParseError> {
+    let mut reader = JsonReader {
+        chars: text.chars().collect(),
+        index: 0,
+        line: 1,
+        column: 1,
+    };
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    match reader.peek() {
+        None => Ok(value),
+        Some(_) => Err(reader.error("unexpected text after the JSON value")),
+    }
+}
+
+struct JsonReader {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+impl JsonReader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.index += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> AlmanacParseError {
+        let text = match self.peek() {
+            Some(c) => c.to_string(),
+            None => "end of text".to_owned(),
+        };
+        AlmanacParseError {
+            line: self.line,
+            column: self.column,
+            text,
+            kind: AlmanacParseErrorKind::Json(message.to_owned()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), AlmanacParseError> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{expected}'")));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<JsonValue, AlmanacParseError> {
+        self.skip_whitespace();
+        let (line, column) = (self.line, self.column);
+        let kind = match self.peek() {
+            Some('{') => self.object()?,
+            Some('[') => self.array()?,
+            Some('"') => JsonKind::String(self.string()?),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.literal("true", JsonKind::Bool(true))?,
+            Some('f') => self.literal("false", JsonKind::Bool(false))?,
+            Some('n') => self.literal("null", JsonKind::Null)?,
+            _ => return Err(self.error("expected a JSON value")),
+        };
+        Ok(JsonValue { line, column, kind })
+    }
+
+    fn object(&mut self) -> Result<JsonKind, AlmanacParseError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(JsonKind::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some('}') => {
+                    self.next();
+                    return Ok(JsonKind::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonKind, AlmanacParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(JsonKind::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some(']') => {
+                    self.next();
+                    return Ok(JsonKind::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, AlmanacParseError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.next();
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.next();
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.next();
+                            s.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.next();
+                    s.push(escaped);
+                }
+                Some(c) => {
+                    self.next();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    // Reads the hex digits of a `\u` escape, including the second half of a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, AlmanacParseError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.peek() != Some('\\') {
+                return Err(self.error("expected the second half of a surrogate pair"));
+            }
+            self.next();
+            self.expect('u')?;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("invalid second half of a surrogate pair"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, AlmanacParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("expected a hex digit"))?;
+            self.next();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> JsonKind {
+        let mut n = String::new();
+        while let Some(c) = self
+            .peek()
+            .filter(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+        {
+            self.next();
+            n.push(c);
+        }
+        JsonKind::Number(n)
+    }
+
+    fn literal(&mut self, word: &str, kind: JsonKind) -> Result<JsonKind, AlmanacParseError> {
+        for expected in word.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error(&format!("expected '{word}'")));
+            }
+            self.next();
+        }
+        Ok(kind)
+    }
+}
@@ -0,0 +1,360 @@
+//! Checks of the maps in almanac text, for almanacs that have been edited by hand.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use super::{
+    merge_ranges, AlmanacBuilder, AlmanacBuilderError, AlmanacDraft, DraftMap, Mapping,
+    MappingTable, SEED_CATEGORY,
+};
+
+#[cfg(test)]
+mod test {
+    use super::super::test::ALMANAC_TEXT;
+    use super::*;
+
+    fn lint(almanac_text: &str) -> LintReport {
+        AlmanacBuilder::new()
+            .text(almanac_text)
+            .lint()
+            .expect("almanac text should parse")
+    }
+
+    #[test]
+    fn day5_lint_reports_mappings_the_example_seeds_never_reach() {
+        let report = lint(ALMANAC_TEXT);
+
+        assert_eq!(report.maps().len(), 7);
+        assert!(report
+            .maps()
+            .iter()
+            .all(|map| map.overlaps.is_empty() && map.zero_length.is_empty()));
+        // None of the seeds 79, 14, 55 and 13, or the ranges 79..93 and 55..68, are 98 or 99.
+        assert_eq!(
+            report.maps()[0].unreachable,
+            vec![LintedMapping {
+                line: 5,
+                mapping: Mapping::new(50, 98, 2),
+            }]
+        );
+        assert!(report.maps()[0].is_contiguous);
+        assert_eq!(report.maps()[0].identity_ranges, vec![0..50, 100..u64::MAX]);
+    }
+
+    #[test]
+    fn day5_lint_reports_problems_in_each_map() {
+        let report = lint(
+            r"
+seeds: 10 5
+
+seed-to-soil map:
+100 10 5
+200 12 4
+300 40 0
+400 50 10
+
+soil-to-location map:
+0 100 2
+        ",
+        );
+
+        let map = &report.maps()[0];
+        assert_eq!(
+            map.overlaps,
+            vec![(
+                LintedMapping {
+                    line: 5,
+                    mapping: Mapping::new(100, 10, 5),
+                },
+                LintedMapping {
+                    line: 6,
+                    mapping: Mapping::new(200, 12, 4),
+                },
+            )]
+        );
+        assert!(!map.is_contiguous);
+        assert_eq!(map.identity_ranges, vec![0..10, 16..50, 60..u64::MAX]);
+        assert_eq!(
+            map.zero_length,
+            vec![LintedMapping {
+                line: 7,
+                mapping: Mapping::new(300, 40, 0),
+            }]
+        );
+        assert_eq!(
+            map.unreachable,
+            vec![LintedMapping {
+                line: 8,
+                mapping: Mapping::new(400, 50, 10),
+            }]
+        );
+        assert!(report.maps()[1].unreachable.is_empty());
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn day5_lint_report_renders_as_text() {
+        let report = lint("seeds: 0 1\nseed-to-soil map:\n5 0 0\n7 3 2\n");
+
+        assert_eq!(
+            report.to_string(),
+            "\
+seed-to-soil map:
+  contiguous: yes
+  identity: 0..3, 5..18446744073709551615
+  zero-length: line 3 (5 0 0)
+  unreachable: line 4 (7 3 2)
+"
+        );
+    }
+}
+
+/// The findings of checking every map in an almanac.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintReport {
+    maps: Vec<MapLint>,
+}
+
+/// The findings of checking a single map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapLint {
+    /// The category the map takes values from.
+    pub source: String,
+
+    /// The category the map gives values in.
+    pub destination: String,
+
+    /// Pairs of mappings whose source ranges overlap, the one appearing first in the text first.
+    pub overlaps: Vec<(LintedMapping, LintedMapping)>,
+
+    /// Whether the mapped source values form a single range without gaps.
+    pub is_contiguous: bool,
+
+    /// The ranges of source values that no mapping covers, which pass through unchanged.
+    pub identity_ranges: Vec<Range<u64>>,
+
+    /// Mappings with a count of zero, which don't map anything.
+    pub zero_length: Vec<LintedMapping>,
+
+    /// Mappings whose source values can't come from any of the seeds.
+    pub unreachable: Vec<LintedMapping>,
+}
+
+/// A mapping along with the line of the almanac text it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintedMapping {
+    /// The 1-based line number of the mapping.
+    pub line: usize,
+
+    /// The mapping.
+    pub mapping: Mapping,
+}
+
+impl LintReport {
+    /// The findings for each map, in the order the maps appear in the text.
+    pub fn maps(&self) -> &[MapLint] {
+        &self.maps
+    }
+
+    /// Whether no map has overlapping, zero-length or unreachable mappings.
+    ///
+    /// Gaps and identity ranges are normal in an almanac, so they don't count against it.
+    pub fn is_clean(&self) -> bool {
+        self.maps.iter().all(|map| {
+            map.overlaps.is_empty() && map.zero_length.is_empty() && map.unreachable.is_empty()
+        })
+    }
+}
+
+impl Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for map in &self.maps {
+            writeln!(f, "{}-to-{} map:", map.source, map.destination)?;
+            writeln!(
+                f,
+                "  contiguous: {}",
+                if map.is_contiguous { "yes" } else { "no" }
+            )?;
+            if !map.identity_ranges.is_empty() {
+                let ranges = map
+                    .identity_ranges
+                    .iter()
+                    .map(|range| format!("{}..{}", range.start, range.end))
+                    .collect::<Vec<_>>();
+                writeln!(f, "  identity: {}", ranges.join(", "))?;
+            }
+            for (first, second) in &map.overlaps {
+                writeln!(f, "  overlap: {first} overlaps {second}")?;
+            }
+            for linted in &map.zero_length {
+                writeln!(f, "  zero-length: {linted}")?;
+            }
+            for linted in &map.unreachable {
+                writeln!(f, "  unreachable: {linted}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for LintedMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} ({} {} {})",
+            self.line, self.mapping.destination, self.mapping.source, self.mapping.count
+        )
+    }
+}
+
+impl AlmanacBuilder {
+    /// Checks the maps in the text or JSON for overlaps, gaps, zero-length mappings and mappings no seed can reach.
+    ///
+    /// Unlike [`AlmanacBuilder::build`], overlapping mappings are reported rather than rejected, so any input that
+    /// parses can be checked. The seeds are also read as ranges, so a seed range running past `u64::MAX` is an error.
+    pub fn lint(&self) -> Result<LintReport, AlmanacBuilderError> {
+        let draft = self.draft()?;
+        draft
+            .check_seed_range_overflow()
+            .map_err(AlmanacBuilderError::Parse)?;
+
+        Ok(LintReport::from(&draft))
+    }
+}
+
+impl From<&AlmanacDraft> for LintReport {
+    fn from(value: &AlmanacDraft) -> Self {
+        let reachable = get_reachable_ranges(value);
+        let maps = value
+            .maps
+            .iter()
+            .map(|map| lint_map(map, reachable.get(map.source.as_str())))
+            .collect();
+        Self { maps }
+    }
+}
+
+fn lint_map(map: &DraftMap, reachable: Option<&Vec<Range<u64>>>) -> MapLint {
+    let linted = map
+        .rows
+        .iter()
+        .map(|row| LintedMapping {
+            line: row.line,
+            mapping: row.mapping,
+        })
+        .collect::<Vec<_>>();
+    let (zero_length, mapped): (Vec<_>, Vec<_>) =
+        linted.into_iter().partition(|l| l.mapping.count == 0);
+
+    let mut overlaps = Vec::new();
+    for (i, first) in mapped.iter().enumerate() {
+        for second in &mapped[i + 1..] {
+            let first_range = first.mapping.source_range();
+            let second_range = second.mapping.source_range();
+            if first_range.start < second_range.end && second_range.start < first_range.end {
+                overlaps.push((*first, *second));
+            }
+        }
+    }
+
+    let covered = merge_ranges(
+        mapped
+            .iter()
+            .map(|l| l.mapping.source_range())
+            .collect::<Vec<_>>(),
+    );
+    let mut identity_ranges = Vec::new();
+    let mut start = 0;
+    for range in &covered {
+        if start < range.start {
+            identity_ranges.push(start..range.start);
+        }
+        start = range.end;
+    }
+    if start < u64::MAX {
+        identity_ranges.push(start..u64::MAX);
+    }
+
+    let unreachable = mapped
+        .iter()
+        .filter(|l| {
+            let source_range = l.mapping.source_range();
+            !reachable.is_some_and(|ranges| {
+                ranges
+                    .iter()
+                    .any(|r| r.start < source_range.end && source_range.start < r.end)
+            })
+        })
+        .copied()
+        .collect();
+
+    MapLint {
+        source: map.source.clone(),
+        destination: map.destination.clone(),
+        overlaps,
+        is_contiguous: covered.len() <= 1,
+        identity_ranges,
+        zero_length,
+        unreachable,
+    }
+}
+
+/// Works out which values of each category can come from the seeds, whether the seeds are read as single numbers or
+/// as ranges.
+///
+/// Where mappings overlap, the one appearing first in the text wins. Values are followed through chains of at most as
+/// many maps as there are in the almanac, which covers every chain that doesn't go round in a circle. The seed ranges
+/// must already have been checked for overflow.
+fn get_reachable_ranges(draft: &AlmanacDraft) -> HashMap<&str, Vec<Range<u64>>> {
+    let tables = draft
+        .maps
+        .iter()
+        .map(|map| {
+            let mut table = MappingTable::new();
+            for row in &map.rows {
+                // Overlapping mappings are reported separately; here the first one wins.
+                let _ = table.insert(row.mapping);
+            }
+            table
+        })
+        .collect::<Vec<_>>();
+
+    let seed_ranges = draft
+        .seeds
+        .chunks_exact(2)
+        .map(|pair| pair[0]..pair[0] + pair[1]);
+    // No mapping covers `u64::MAX`, so a seed that can't be held in a range has no mappings to reach.
+    let seed_numbers = draft
+        .seeds
+        .iter()
+        .filter_map(|seed| Some(*seed..seed.checked_add(1)?));
+    let mut reachable = HashMap::from([(
+        SEED_CATEGORY,
+        merge_ranges(seed_ranges.chain(seed_numbers).collect()),
+    )]);
+
+    for _ in 0..draft.maps.len() {
+        let mut changed = false;
+        for (map, table) in draft.maps.iter().zip(&tables) {
+            let Some(source_ranges) = reachable.get(map.source.as_str()) else {
+                continue;
+            };
+            let destination_ranges = table.get_ranges(source_ranges);
+            let existing = reachable
+                .get(map.destination.as_str())
+                .cloned()
+                .unwrap_or_default();
+            let merged = merge_ranges(existing.iter().cloned().chain(destination_ranges).collect());
+            if merged != existing {
+                reachable.insert(map.destination.as_str(), merged);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    reachable
+}
@@ -0,0 +1,407 @@
+//! Writing almanacs as canonical almanac text, and reading and writing them as JSON.
+
+use solution::json::push_json_string;
+
+use super::json::parse_json;
+use super::{
+    is_valid_category_name, Almanac, AlmanacDraft, AlmanacParseError, AlmanacParseErrorKind,
+    DraftMap, DraftRow, DraftSeed, Mapping,
+};
+
+// Only impl blocks follow the tests, which clippy would otherwise flag.
+#[allow(clippy::items_after_test_module)]
+#[cfg(test)]
+mod test {
+    use super::super::test::{generate_almanac_text, ALMANAC_TEXT};
+    use super::super::{AlmanacBuilder, AlmanacBuilderError};
+    use super::*;
+    use solution::rng::XorShift;
+
+    fn build_text(almanac_text: &str) -> Almanac {
+        AlmanacBuilder::new()
+            .text(almanac_text)
+            .build()
+            .expect("almanac text should build")
+    }
+
+    #[test]
+    fn day5_to_text_writes_canonical_almanac_text() {
+        let almanac = build_text(
+            r"
+humidity-to-location map:
+7 3 1
+
+seeds: 1 2
+seed-to-humidity map:
+1000 5 10
+2 20 0
+0 0 5
+5 5 0
+        ",
+        );
+
+        assert_eq!(
+            almanac.to_text(),
+            "\
+seeds: 1 2
+
+humidity-to-location map:
+7 3 1
+
+seed-to-humidity map:
+   0  0  5
+1000  5 10
+   2 20  0
+   5  5  0
+"
+        );
+    }
+
+    #[test]
+    fn day5_parse_write_parse_gives_an_identical_database() {
+        let almanac = build_text(ALMANAC_TEXT);
+        assert_eq!(build_text(&almanac.to_text()), almanac);
+
+        let mut rng = XorShift::new(0x7e47_7e47);
+        for _ in 0..50 {
+            let almanac = build_text(&generate_almanac_text(&mut rng));
+            assert_eq!(build_text(&almanac.to_text()), almanac);
+        }
+    }
+
+    // Shuffles the order of the maps in almanac text, and the order of the rows within each map.
+    fn shuffle_almanac_text(rng: &mut XorShift, almanac_text: &str) -> String {
+        fn shuffle<T>(rng: &mut XorShift, items: &mut [T]) {
+            for i in (1..items.len()).rev() {
+                items.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+            }
+        }
+
+        let mut sections = almanac_text.split("\n\n").collect::<Vec<_>>();
+        let mut maps = sections
+            .split_off(1)
+            .into_iter()
+            .map(|section| {
+                let mut lines = section.lines().collect::<Vec<_>>();
+                shuffle(rng, &mut lines[1..]);
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>();
+        shuffle(rng, &mut maps);
+
+        sections[0].to_owned() + "\n\n" + &maps.join("\n\n") + "\n"
+    }
+
+    #[test]
+    fn day5_round_trips_keep_the_order_of_shuffled_maps() {
+        let mut rng = XorShift::new(0x5417_f1ed);
+        for _ in 0..50 {
+            let text = generate_almanac_text(&mut rng);
+            let text = shuffle_almanac_text(&mut rng, &text);
+            let almanac = build_text(&text);
+
+            assert_eq!(build_text(&almanac.to_text()), almanac);
+            let read_back = AlmanacBuilder::new()
+                .json(almanac.to_json())
+                .build()
+                .expect("almanac json should build");
+            assert_eq!(read_back, almanac);
+        }
+    }
+
+    #[test]
+    fn day5_json_category_names_must_survive_the_text_round_trip() {
+        let json = |source: &str, destination: &str| {
+            let mut json = String::from(r#"{"seeds": [1], "maps": [{"source": "#);
+            push_json_string(&mut json, source);
+            json.push_str(r#", "destination": "#);
+            push_json_string(&mut json, destination);
+            json.push_str(r#", "mappings": [{"destination": 5, "source": 1, "count": 1}]}]}"#);
+            AlmanacBuilder::new().json(json).build()
+        };
+
+        for (source, destination) in [
+            ("seed", "soil:"),
+            ("seed", "so: il"),
+            (" seed", "soil"),
+            ("seed", "soil "),
+            ("se\ned", "soil"),
+            ("seed", "so\ril"),
+        ] {
+            let result = json(source, destination);
+            let category = if is_valid_category_name(source) {
+                destination
+            } else {
+                source
+            };
+            assert!(
+                matches!(
+                    &result,
+                    Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                        kind: AlmanacParseErrorKind::CategoryName,
+                        text,
+                        ..
+                    })) if text == category
+                ),
+                "{source:?}-to-{destination:?}: {result:?}"
+            );
+        }
+
+        for (source, destination) in [
+            ("seed", "soil"),
+            ("se ed", "so\til"),
+            ("", "a:b"),
+            ("seeds", "map"),
+            ("seed\tx", "soil"),
+        ] {
+            let almanac = json(source, destination).expect("almanac json should build");
+            assert_eq!(
+                build_text(&almanac.to_text()),
+                almanac,
+                "{source:?}-to-{destination:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn day5_category_names_cannot_contain_the_separator() {
+        let text = AlmanacBuilder::new()
+            .text("seeds: 1\nseed-to-soil-to-water map:\n")
+            .build();
+        let json = AlmanacBuilder::new()
+            .json(r#"{"seeds": [1], "maps": [{"source": "seed-to-soil", "destination": "water", "mappings": []}]}"#)
+            .build();
+
+        assert_eq!(
+            text,
+            Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                line: 2,
+                column: 1,
+                text: "seed-to-soil-to-water map:".to_owned(),
+                kind: AlmanacParseErrorKind::CategoryName,
+            }))
+        );
+        assert_eq!(
+            json,
+            Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                line: 1,
+                column: 25,
+                text: "seed-to-soil".to_owned(),
+                kind: AlmanacParseErrorKind::CategoryName,
+            }))
+        );
+    }
+
+    #[test]
+    fn day5_json_round_trip_gives_an_identical_database() {
+        let almanac = build_text(
+            "seeds: 79 14\nseed-to-soil map:\n50 98 2\n52 50 48\n1 2 0\nsoil-\"to\"-to-x map:\n",
+        );
+
+        let json = almanac.to_json();
+        let read_back = AlmanacBuilder::new()
+            .json(json.as_str())
+            .build()
+            .expect("almanac json should build");
+
+        assert_eq!(read_back, almanac);
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"seeds":[79,14],"maps":["#,
+                r#"{"source":"seed","destination":"soil","mappings":["#,
+                r#"{"destination":52,"source":50,"count":48},"#,
+                r#"{"destination":50,"source":98,"count":2},"#,
+                r#"{"destination":1,"source":2,"count":0}]},"#,
+                r#"{"source":"soil-\"to\"","destination":"x","mappings":[]}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn day5_json_input_reports_where_it_is_wrong() {
+        let result = AlmanacBuilder::new()
+            .json("{\"seeds\": [1],\n \"maps\": [{\"source\": \"seed\", \"destination\": \"soil\",\n   \"mappings\": [{\"destination\": 1, \"source\": -2, \"count\": 3}]}]}")
+            .build();
+
+        assert_eq!(
+            result,
+            Err(AlmanacBuilderError::Parse(AlmanacParseError {
+                line: 3,
+                column: 46,
+                text: "-2".to_owned(),
+                kind: AlmanacParseErrorKind::Json(
+                    "expected a whole number that fits in a u64".to_owned()
+                ),
+            }))
+        );
+    }
+}
+
+impl Almanac {
+    /// Writes the almanac as canonical almanac text.
+    ///
+    /// The seeds come first, then the maps in the order they appeared. The mappings of each map are sorted by source,
+    /// with zero-length mappings last, and their numbers are aligned in columns.
+    ///
+    /// Reading the text back gives an identical almanac.
+    pub fn to_text(&self) -> String {
+        let seeds = self.db.seeds.iter().map(u64::to_string).collect::<Vec<_>>();
+        let mut text = format!("seeds: {}\n", seeds.join(" "));
+
+        for map in &self.db.maps {
+            text.push_str(&format!("\n{}-to-{} map:\n", map.source, map.destination));
+
+            let rows = map
+                .table
+                .mappings()
+                .iter()
+                .chain(map.table.empty_mappings())
+                .map(|m| [m.destination, m.source, m.count].map(|n| n.to_string()))
+                .collect::<Vec<_>>();
+            let mut widths = [0; 3];
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+            for [destination, source, count] in &rows {
+                text.push_str(&format!(
+                    "{destination:>0$} {source:>1$} {count:>2$}\n",
+                    widths[0], widths[1], widths[2]
+                ));
+            }
+        }
+
+        text
+    }
+
+    /// Writes the almanac as JSON, which [`super::AlmanacBuilder::json`] can read back.
+    ///
+    /// The maps are written in the order they appeared, and reading the JSON back gives an identical almanac.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from(r#"{"seeds":["#);
+        let seeds = self.db.seeds.iter().map(u64::to_string).collect::<Vec<_>>();
+        json.push_str(&seeds.join(","));
+        json.push_str(r#"],"maps":["#);
+
+        for (i, map) in self.db.maps.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"source":"#);
+            push_json_string(&mut json, &map.source);
+            json.push_str(r#","destination":"#);
+            push_json_string(&mut json, &map.destination);
+            json.push_str(r#","mappings":["#);
+            let mappings = map
+                .table
+                .mappings()
+                .iter()
+                .chain(map.table.empty_mappings())
+                .map(|m| {
+                    format!(
+                        r#"{{"destination":{},"source":{},"count":{}}}"#,
+                        m.destination, m.source, m.count
+                    )
+                })
+                .collect::<Vec<_>>();
+            json.push_str(&mappings.join(","));
+            json.push_str("]}");
+        }
+
+        json.push_str("]}");
+        json
+    }
+}
+
+impl AlmanacDraft {
+    /// Reads an almanac from JSON as written by [`Almanac::to_json`].
+    pub(super) fn from_json(json: &str) -> Result<Self, AlmanacParseError> {
+        let root = parse_json(json)?;
+
+        let seed_values = root.member("seeds")?.as_array()?;
+        let seeds = seed_values
+            .iter()
+            .map(|seed| seed.as_u64())
+            .collect::<Result<Vec<_>, _>>()?;
+        let seed_positions = seed_values
+            .iter()
+            .zip(&seeds)
+            .map(|(value, seed)| DraftSeed {
+                line: value.line,
+                column: value.column,
+                text: seed.to_string(),
+            })
+            .collect();
+
+        let mut maps: Vec<DraftMap> = Vec::new();
+        for map in root.member("maps")?.as_array()? {
+            let source = map.member("source")?.as_str()?.to_owned();
+            let destination = map.member("destination")?.as_str()?.to_owned();
+            if let Some(category) = [&source, &destination]
+                .into_iter()
+                .find(|category| !is_valid_category_name(category))
+            {
+                return Err(AlmanacParseError {
+                    line: map.line,
+                    column: map.column,
+                    text: category.clone(),
+                    kind: AlmanacParseErrorKind::CategoryName,
+                });
+            }
+            if maps
+                .iter()
+                .any(|m| m.source == source && m.destination == destination)
+            {
+                return Err(AlmanacParseError {
+                    line: map.line,
+                    column: map.column,
+                    text: format!("{source}-to-{destination}"),
+                    kind: AlmanacParseErrorKind::DuplicateSection,
+                });
+            }
+
+            let rows = map
+                .member("mappings")?
+                .as_array()?
+                .iter()
+                .map(|row| {
+                    let mapping = Mapping::new(
+                        row.member("destination")?.as_u64()?,
+                        row.member("source")?.as_u64()?,
+                        row.member("count")?.as_u64()?,
+                    );
+                    let text = format!(
+                        "{} {} {}",
+                        mapping.destination, mapping.source, mapping.count
+                    );
+                    mapping.check_overflow().map_err(|e| AlmanacParseError {
+                        line: row.line,
+                        column: row.column,
+                        text: text.clone(),
+                        kind: AlmanacParseErrorKind::Mapping(e),
+                    })?;
+                    Ok(DraftRow {
+                        line: row.line,
+                        column: row.column,
+                        text,
+                        mapping,
+                    })
+                })
+                .collect::<Result<Vec<_>, AlmanacParseError>>()?;
+
+            maps.push(DraftMap {
+                source,
+                destination,
+                rows,
+            });
+        }
+
+        Ok(Self {
+            seeds,
+            seed_positions,
+            maps,
+        })
+    }
+}
@@ -0,0 +1,274 @@
+//! Traces of single values through the maps of the almanac.
+
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+use solution::json::push_json_string;
+
+use super::{Almanac, AlmanacError, Mapping, LOCATION_CATEGORY, SEED_CATEGORY};
+
+#[cfg(test)]
+mod test {
+    use super::super::test::ALMANAC_TEXT;
+    use super::super::AlmanacBuilder;
+    use super::*;
+
+    #[test]
+    fn day5_trace_records_every_map_a_seed_goes_through() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        let trace = almanac.trace(79).expect("seeds should lead to locations");
+
+        let values = trace
+            .steps()
+            .iter()
+            .map(|step| (step.input, step.output))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                (79, 81),
+                (81, 81),
+                (81, 81),
+                (81, 74),
+                (74, 78),
+                (78, 78),
+                (78, 82)
+            ]
+        );
+        assert_eq!(trace.steps()[0].mapping, Some(Mapping::new(52, 50, 48)));
+        assert_eq!(trace.steps()[1].mapping, None);
+        assert_eq!(trace.output(), Some(82));
+    }
+
+    #[test]
+    fn day5_trace_renders_as_a_table() {
+        let almanac = AlmanacBuilder::new()
+            .text(ALMANAC_TEXT)
+            .build()
+            .expect("almanac should build");
+
+        let trace = almanac.trace(14).expect("seeds should lead to locations");
+        let table = trace.render(TraceFormat::Table);
+
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "map                      input  mapping   output");
+        assert_eq!(lines[1], "seed-to-soil             14     identity  14");
+        assert_eq!(lines[2], "soil-to-fertilizer       14     39 0 15   53");
+    }
+
+    #[test]
+    fn day5_trace_aligns_categories_that_are_not_ascii() {
+        let almanac = AlmanacBuilder::new()
+            .text("seeds: 1\nseed-to-größe map:\n10 0 5\ngröße-to-location map:\n")
+            .build()
+            .expect("almanac should build");
+
+        let trace = almanac.trace(3).expect("seeds should lead to locations");
+        let table = trace.render(TraceFormat::Table);
+
+        assert_eq!(
+            table.lines().collect::<Vec<_>>(),
+            vec![
+                "map                input  mapping   output",
+                "seed-to-größe      3      10 0 5    13",
+                "größe-to-location  13     identity  13",
+            ]
+        );
+    }
+
+    #[test]
+    fn day5_trace_renders_as_json() {
+        let almanac = AlmanacBuilder::new()
+            .text("seeds: 1\nseed-to-soil map:\n10 0 5\nsoil-to-location map:\n")
+            .build()
+            .expect("almanac should build");
+
+        let trace = almanac.trace(3).expect("seeds should lead to locations");
+
+        assert_eq!(
+            trace.render(TraceFormat::Json),
+            concat!(
+                r#"{"steps":["#,
+                r#"{"source":"seed","destination":"soil","input":3,"#,
+                r#""mapping":{"destination":10,"source":0,"count":5},"output":13},"#,
+                r#"{"source":"soil","destination":"location","input":13,"mapping":null,"output":13}"#,
+                r#"]}"#
+            )
+        );
+    }
+}
+
+/// The path of a single value through each map in a chain of maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+}
+
+/// A single map that a traced value went through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The category of the input value.
+    pub source: String,
+
+    /// The category of the output value.
+    pub destination: String,
+
+    /// The value going into the map.
+    pub input: u64,
+
+    /// The mapping that matched the input value, or `None` if the value passed through unchanged.
+    pub mapping: Option<Mapping>,
+
+    /// The value coming out of the map.
+    pub output: u64,
+}
+
+/// The ways a trace can be rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// A table with one row per map, for people to read.
+    #[default]
+    Table,
+
+    /// A JSON object with one entry per map, for tools to read.
+    Json,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown trace format '{other}', expected 'table' or 'json'"
+            )),
+        }
+    }
+}
+
+impl Trace {
+    /// The steps of the trace, one per map in the chain.
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// The value at the end of the chain, or `None` if the chain has no maps.
+    pub fn output(&self) -> Option<u64> {
+        self.steps.last().map(|step| step.output)
+    }
+
+    /// Renders the trace in the given format.
+    pub fn render(&self, format: TraceFormat) -> String {
+        match format {
+            TraceFormat::Table => self.to_string(),
+            TraceFormat::Json => self.to_json(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = String::from(r#"{"steps":["#);
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"source":"#);
+            push_json_string(&mut json, &step.source);
+            json.push_str(r#","destination":"#);
+            push_json_string(&mut json, &step.destination);
+            json.push_str(&format!(r#","input":{},"mapping":"#, step.input));
+            match &step.mapping {
+                Some(mapping) => json.push_str(&format!(
+                    r#"{{"destination":{},"source":{},"count":{}}}"#,
+                    mapping.destination, mapping.source, mapping.count
+                )),
+                None => json.push_str("null"),
+            }
+            json.push_str(&format!(r#","output":{}}}"#, step.output));
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = ["map", "input", "mapping", "output"];
+        let rows = self
+            .steps
+            .iter()
+            .map(|step| {
+                [
+                    format!("{}-to-{}", step.source, step.destination),
+                    step.input.to_string(),
+                    match &step.mapping {
+                        Some(mapping) => format!(
+                            "{} {} {}",
+                            mapping.destination, mapping.source, mapping.count
+                        ),
+                        None => "identity".to_owned(),
+                    },
+                    step.output.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        // Every column but the last is padded to its widest cell, plus a gap. Padding counts characters, not bytes.
+        let mut widths = header.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let header = header.map(str::to_owned);
+        for row in std::iter::once(&header).chain(&rows) {
+            let mut line = String::new();
+            for (i, cell) in row.iter().enumerate() {
+                if i + 1 == row.len() {
+                    line.push_str(cell);
+                } else {
+                    write!(line, "{cell:width$}", width = widths[i] + 2)?;
+                }
+            }
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Almanac {
+    /// Traces a value from one category to another through each map in the chain between them.
+    pub fn trace_value(&self, value: u64, from: &str, to: &str) -> Result<Trace, AlmanacError> {
+        let path = self.db.find_path(from, to)?;
+        let mut input = value;
+        let steps = path
+            .iter()
+            .map(|map| {
+                let mapping = map.table.find(input).copied();
+                let output = map.table.get(input);
+                let step = TraceStep {
+                    source: map.source.clone(),
+                    destination: map.destination.clone(),
+                    input,
+                    mapping,
+                    output,
+                };
+                input = output;
+                step
+            })
+            .collect();
+        Ok(Trace { steps })
+    }
+
+    /// Traces a seed through each map on the way to its location.
+    pub fn trace(&self, seed: u64) -> Result<Trace, AlmanacError> {
+        self.trace_value(seed, SEED_CATEGORY, LOCATION_CATEGORY)
+    }
+}
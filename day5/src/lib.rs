@@ -0,0 +1,64 @@
+//! Day 5: If You Give A Seed A Fertilizer
+
+use solution::{Answer, Solution};
+use std::error::Error;
+
+pub mod almanac;
+
+/// Solution for day 5.
+pub struct Day5;
+
+impl Solution for Day5 {
+    fn day(&self) -> u32 {
+        5
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        let almanac = almanac::AlmanacBuilder::new().text(input).build()?;
+        Ok(Box::new(
+            almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_number()?,
+        ))
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>> {
+        let almanac = almanac::AlmanacBuilder::new()
+            .text(input)
+            .seed_ranges(true)
+            .build()?;
+        Ok(Box::new(
+            almanac.get_lowest_location_number_that_corresponds_to_any_initial_seed_range()?,
+        ))
+    }
+}
+
+/// Runs one part of the puzzle from the command line: reads the almanac from the file named by the first argument and
+/// prints the answer found by `solve` after its description.
+///
+/// Optionally traces a seed through every map as well, e.g. `--trace 79 --format json`.
+pub fn run(args: &[String], description: &str, solve: fn(&str) -> u64) {
+    let file_path = args
+        .get(1)
+        .expect("Please supply an input file as the first argument");
+    let input = std::fs::read_to_string(file_path)
+        .unwrap_or_else(|err| panic!("Error reading file '{file_path}': {err:?}"));
+    let value = solve(&input);
+    println!("{description} = {value}");
+
+    if let Some(i) = args.iter().position(|arg| arg == "--trace") {
+        let seed = args
+            .get(i + 1)
+            .expect("Please supply a seed number after --trace")
+            .parse::<u64>()
+            .unwrap_or_else(|err| panic!("Error parsing seed number to trace: {err:?}"));
+        let format = match args.iter().position(|arg| arg == "--format") {
+            Some(i) => args
+                .get(i + 1)
+                .expect("Please supply a format after --format")
+                .parse::<almanac::TraceFormat>()
+                .unwrap_or_else(|err| panic!("{err}")),
+            None => almanac::TraceFormat::default(),
+        };
+        let trace = almanac::trace_seed(&input, seed);
+        println!("{}", trace.render(format).trim_end());
+    }
+}
@@ -0,0 +1,38 @@
+//! Writing JSON text by hand.
+
+use std::fmt::Write;
+
+// Clippy flags the function below for coming after the tests.
+#[allow(clippy::items_after_test_module)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_json_string_escapes_quotes_backslashes_and_control_characters() {
+        let mut json = String::from("[");
+        push_json_string(&mut json, "say \"hi\"\\\n\t\u{1}é");
+        json.push(']');
+
+        assert_eq!(json, r#"["say \"hi\"\\\n\t\u0001é"]"#);
+    }
+}
+
+/// Appends a string to the JSON text as a quoted and escaped JSON string.
+pub fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
@@ -0,0 +1,24 @@
+//! The common shape of every day's puzzle solution, and helpers that more than one day uses.
+#![warn(missing_docs)]
+#![warn(clippy::unwrap_used)]
+
+use std::error::Error;
+use std::fmt::Display;
+
+pub mod json;
+pub mod rng;
+
+/// The answer to one part of a puzzle.
+pub type Answer = Box<dyn Display>;
+
+/// A day's puzzle solution.
+pub trait Solution {
+    /// The day of the puzzle, starting from 1.
+    fn day(&self) -> u32;
+
+    /// Solves part 1 of the puzzle for the input.
+    fn part1(&self, input: &str) -> Result<Answer, Box<dyn Error>>;
+
+    /// Solves part 2 of the puzzle for the input.
+    fn part2(&self, input: &str) -> Result<Answer, Box<dyn Error>>;
+}
@@ -0,0 +1,65 @@
+//! A small pseudo-random number generator for generating puzzle inputs in tests and benchmarks.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xorshift_gives_the_same_numbers_for_the_same_seed() {
+        let numbers = |seed| {
+            let mut rng = XorShift::new(seed);
+            (0..5).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers(1), numbers(1));
+        assert_ne!(numbers(1), numbers(2));
+        assert!(numbers(0).iter().all(|&n| n != 0));
+    }
+
+    #[test]
+    fn xorshift_stays_within_its_ranges() {
+        let mut rng = XorShift::new(7);
+        for _ in 0..1_000 {
+            assert!(rng.below(10) < 10);
+            assert!((0.0..1.0).contains(&rng.fraction()));
+        }
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+}
+
+/// A small, fast pseudo-random number generator, so generated inputs can be regenerated from their seed.
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// Creates a generator from a seed. Any seed works, including zero.
+    pub fn new(seed: u64) -> Self {
+        // Mix the seed so that small seeds give unrelated sequences, and the state is never zero.
+        let mixed = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xD1B5_4A32_D192_ED03;
+        Self(if mixed == 0 { 1 } else { mixed })
+    }
+
+    /// Returns the next number in the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number from 0 up to but not including `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns a number from 0 up to but not including 1.
+    pub fn fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Returns `true` with the given probability, from 0 to 1.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.fraction() < probability
+    }
+}